@@ -0,0 +1,45 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use kizuna::{FastLocator, Locator};
+use std::hash::BuildHasher;
+
+#[derive(Clone)]
+struct Config {
+    port: u16,
+}
+
+macro_rules! services {
+    ($($name:ident),*) => {
+        $(
+            #[derive(Clone)]
+            struct $name;
+        )*
+
+        fn populate<S: BuildHasher>(locator: &mut Locator<S>) {
+            $(locator.insert($name);)*
+            locator.insert(Config { port: 8080 });
+        }
+    };
+}
+
+services!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
+
+fn bench_get(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get");
+
+    let mut locator = Locator::new();
+    populate(&mut locator);
+    group.bench_function("default hasher", |b| {
+        b.iter(|| black_box(locator.get::<Config>().unwrap().port))
+    });
+
+    let mut fast = FastLocator::default();
+    populate(&mut fast);
+    group.bench_function("fxhash", |b| {
+        b.iter(|| black_box(fast.get::<Config>().unwrap().port))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_get);
+criterion_main!(benches);
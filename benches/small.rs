@@ -0,0 +1,58 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use kizuna::{Locator, SmallLocator};
+
+#[derive(Clone)]
+struct Service<const N: usize>;
+
+#[derive(Clone)]
+struct Config {
+    port: u16,
+}
+
+macro_rules! populate {
+    ($locator:expr, $len:expr, [$($n:literal),*]) => {
+        $(
+            if $n < $len {
+                $locator.insert(Service::<$n>);
+            }
+        )*
+        $locator.insert(Config { port: 8080 });
+    };
+}
+
+fn bench_small(c: &mut Criterion) {
+    let mut group = c.benchmark_group("small");
+
+    for len in [1, 2, 4, 8, 16, 32] {
+        let mut locator = Locator::new();
+        populate!(
+            locator,
+            len - 1,
+            [
+                0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22,
+                23, 24, 25, 26, 27, 28, 29, 30
+            ]
+        );
+        group.bench_with_input(BenchmarkId::new("Locator", len), &locator, |b, locator| {
+            b.iter(|| black_box(locator.get::<Config>().unwrap().port))
+        });
+
+        let mut small = SmallLocator::new();
+        populate!(
+            small,
+            len - 1,
+            [
+                0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22,
+                23, 24, 25, 26, 27, 28, 29, 30
+            ]
+        );
+        group.bench_with_input(BenchmarkId::new("SmallLocator", len), &small, |b, small| {
+            b.iter(|| black_box(small.get::<Config>().unwrap().port))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_small);
+criterion_main!(benches);
@@ -32,9 +32,10 @@ async fn main() {
 // basic handler that responds with a static string
 #[debug_handler]
 async fn get_users(Extension(locator): Extension<Arc<Locator>>) -> Response {
-    let repo = locator
-        .get::<Box<dyn UserRepository + Send + Sync>>()
-        .expect("unable to get user repository");
+    let repo = match locator.resolve::<Box<dyn UserRepository + Send + Sync>>() {
+        Ok(repo) => repo,
+        Err(err) => return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    };
 
     match repo.get_all().await {
         Ok(users) => Json(users).into_response(),
@@ -47,9 +48,10 @@ async fn create_user(
     Extension(locator): Extension<Arc<Locator>>,
     Json(payload): Json<CreateUser>,
 ) -> Response {
-    let mut repo = locator
-        .get::<Box<dyn UserRepository + Send + Sync>>()
-        .expect("unable to get user repository");
+    let mut repo = match locator.resolve::<Box<dyn UserRepository + Send + Sync>>() {
+        Ok(repo) => repo,
+        Err(err) => return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    };
 
     match repo.save(payload).await {
         Ok(user) => Json(user).into_response(),
@@ -73,11 +75,8 @@ async fn create_locator() -> Locator {
             .unwrap();
 
         locator.insert(pool);
-        locator.insert_with::<_, Box<dyn UserRepository + Send + Sync>>(|locator| {
-            let pool = locator
-                .get::<Pool<Postgres>>()
-                .expect("failed to get in postgres pool");
-            Box::new(PostgresUserRepository::new(pool))
+        kizuna::bind_trait!(locator, UserRepository: Send + Sync, from |pool: Pool<Postgres>| {
+            PostgresUserRepository::new(pool)
         });
 
         tracing::info!("Using postgres database");
@@ -89,11 +88,8 @@ async fn create_locator() -> Locator {
 
         let db = MemoryDb::default();
         locator.insert(db);
-        locator.insert_with::<_, Box<dyn UserRepository + Send + Sync>>(|locator| {
-            let db = locator
-                .get::<MemoryDb>()
-                .expect("failed to get in memory database");
-            Box::new(InMemoryUserRepository::new(db))
+        kizuna::bind_trait!(locator, UserRepository: Send + Sync, from |db: MemoryDb| {
+            InMemoryUserRepository::new(db)
         });
 
         tracing::info!("Using in memory database");
@@ -0,0 +1,326 @@
+//! Derive macros for `kizuna`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, parse_quote, Data, DeriveInput, Field, Fields, FnArg, ItemFn, LitStr, Path,
+    ReturnType, Type,
+};
+
+/// Implements `FromLocator` for a struct resolving each field from the locator.
+///
+/// # Field attributes
+/// - `#[locator(default)]`: uses `Default::default()` when the type is not in the locator.
+/// - `#[locator(named = "name")]`: resolves a value inserted with `insert_named`.
+#[proc_macro_derive(FromLocator, attributes(locator))]
+pub fn derive_from_locator(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand_from_locator(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Adds an inherent `inject(locator: &Locator<S>) -> Result<Self, LocatorError>` constructor to a struct
+/// resolving each field from the locator, the constructor is generic over the hasher of the locator.
+///
+/// # Field attributes
+/// - `#[inject(with = path::to_fn)]`: resolves the field calling `fn(&Locator<S>) -> Result<T, LocatorError>`,
+///   the function must be generic over `S: BuildHasher`.
+/// - `#[inject(clone_from = OtherType)]`: resolves `OtherType` and converts it into the field type using `From`.
+#[proc_macro_derive(Inject, attributes(inject))]
+pub fn derive_inject(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand_inject(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Registers the service returned by a function so it is inserted by `Locator::from_inventory`.
+///
+/// The function takes no arguments or a `&Locator`, and is used as the factory of the service.
+#[proc_macro_attribute]
+pub fn service(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = TokenStream2::from(args);
+    let item = parse_macro_input!(input as ItemFn);
+
+    if !args.is_empty() {
+        return syn::Error::new_spanned(args, "`#[service]` does not take arguments")
+            .to_compile_error()
+            .into();
+    }
+
+    match expand_service(item) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand_service(item: ItemFn) -> syn::Result<TokenStream2> {
+    let sig = &item.sig;
+    let name = &sig.ident;
+
+    if sig.asyncness.is_some() {
+        return Err(syn::Error::new_spanned(
+            sig.asyncness,
+            "`#[service]` cannot be used on async functions",
+        ));
+    }
+
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &sig.generics,
+            "`#[service]` cannot be used on generic functions",
+        ));
+    }
+
+    if let ReturnType::Default = sig.output {
+        return Err(syn::Error::new_spanned(
+            sig,
+            "`#[service]` functions must return the service",
+        ));
+    }
+
+    let factory = match sig.inputs.first() {
+        None => quote! { |_| #name() },
+        Some(input) if sig.inputs.len() == 1 && is_locator_ref(input) => {
+            quote! { |locator| #name(locator) }
+        }
+        Some(_) => {
+            return Err(syn::Error::new_spanned(
+                &sig.inputs,
+                "`#[service]` functions take no arguments or a `&Locator`",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        #item
+
+        const _: () = {
+            fn __register(locator: &mut ::kizuna::Locator) {
+                locator.insert_with(#factory);
+            }
+
+            ::kizuna::inventory::__inventory::submit! {
+                ::kizuna::inventory::ServiceRegistration::new(__register)
+            }
+        };
+    })
+}
+
+/// Returns `true` if the argument is a `&Locator`.
+fn is_locator_ref(input: &FnArg) -> bool {
+    let FnArg::Typed(arg) = input else {
+        return false;
+    };
+
+    match arg.ty.as_ref() {
+        Type::Reference(reference) if reference.mutability.is_none() => matches!(
+            reference.elem.as_ref(),
+            Type::Path(path) if path.qself.is_none()
+                && path.path.segments.last().is_some_and(|segment| segment.ident == "Locator")
+        ),
+        _ => false,
+    }
+}
+
+fn expand_from_locator(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    // The implementation is generic over the hasher of the locator.
+    let mut generics = input.generics.clone();
+    generics
+        .params
+        .push(parse_quote!(__S: ::std::hash::BuildHasher));
+    let (impl_generics, _, _) = generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "`FromLocator` can only be derived for structs",
+            ))
+        }
+    };
+
+    let body = match fields {
+        Fields::Named(fields) => {
+            let values = fields
+                .named
+                .iter()
+                .map(|field| {
+                    let ident = &field.ident;
+                    let value = resolve_field(field)?;
+                    Ok(quote! { #ident: #value })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+
+            quote! { Self { #(#values),* } }
+        }
+        Fields::Unnamed(fields) => {
+            let values = fields
+                .unnamed
+                .iter()
+                .map(resolve_field)
+                .collect::<syn::Result<Vec<_>>>()?;
+
+            quote! { Self ( #(#values),* ) }
+        }
+        Fields::Unit => quote! { Self },
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::kizuna::FromLocator<__S> for #name #ty_generics #where_clause {
+            fn from_locator(locator: &::kizuna::Locator<__S>) -> ::std::result::Result<Self, ::kizuna::LocatorError> {
+                ::std::result::Result::Ok(#body)
+            }
+        }
+    })
+}
+
+fn expand_inject(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "`Inject` can only be derived for structs",
+            ))
+        }
+    };
+
+    let body = match fields {
+        Fields::Named(fields) => {
+            let values = fields
+                .named
+                .iter()
+                .map(|field| {
+                    let ident = &field.ident;
+                    let value = inject_field(field)?;
+                    Ok(quote! { #ident: #value })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+
+            quote! { Self { #(#values),* } }
+        }
+        Fields::Unnamed(fields) => {
+            let values = fields
+                .unnamed
+                .iter()
+                .map(inject_field)
+                .collect::<syn::Result<Vec<_>>>()?;
+
+            quote! { Self ( #(#values),* ) }
+        }
+        Fields::Unit => quote! { Self },
+    };
+
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Creates this type resolving each field from the given locator.
+            pub fn inject<__S: ::std::hash::BuildHasher>(
+                locator: &::kizuna::Locator<__S>,
+            ) -> ::std::result::Result<Self, ::kizuna::LocatorError> {
+                ::std::result::Result::Ok(#body)
+            }
+        }
+    })
+}
+
+enum InjectOptions {
+    Get,
+    With(Path),
+    CloneFrom(Type),
+}
+
+fn inject_options(field: &Field) -> syn::Result<InjectOptions> {
+    let mut options = InjectOptions::Get;
+
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("inject")) {
+        attr.parse_nested_meta(|meta| {
+            if !matches!(options, InjectOptions::Get) {
+                return Err(meta.error("only one of `with` or `clone_from` can be used"));
+            }
+
+            if meta.path.is_ident("with") {
+                options = InjectOptions::With(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("clone_from") {
+                options = InjectOptions::CloneFrom(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `with = path::to_fn` or `clone_from = Type`"))
+            }
+        })?;
+    }
+
+    Ok(options)
+}
+
+fn inject_field(field: &Field) -> syn::Result<TokenStream2> {
+    let ty = &field.ty;
+
+    match inject_options(field)? {
+        InjectOptions::Get => Ok(quote! {
+            locator.get::<#ty>().ok_or_else(|| locator.not_found::<#ty>())?
+        }),
+        InjectOptions::With(path) => Ok(quote! { #path(locator)? }),
+        InjectOptions::CloneFrom(other) => Ok(quote! {
+            <#ty as ::std::convert::From<#other>>::from(
+                locator.get::<#other>().ok_or_else(|| locator.not_found::<#other>())?
+            )
+        }),
+    }
+}
+
+#[derive(Default)]
+struct FieldOptions {
+    default: bool,
+    named: Option<LitStr>,
+}
+
+fn field_options(field: &Field) -> syn::Result<FieldOptions> {
+    let mut options = FieldOptions::default();
+
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("locator")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("default") {
+                options.default = true;
+                Ok(())
+            } else if meta.path.is_ident("named") {
+                options.named = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `default` or `named = \"...\"`"))
+            }
+        })?;
+    }
+
+    Ok(options)
+}
+
+fn resolve_field(field: &Field) -> syn::Result<TokenStream2> {
+    let ty = &field.ty;
+    let options = field_options(field)?;
+
+    let get = match &options.named {
+        Some(name) => quote! { locator.get_named::<#ty>(#name) },
+        None => quote! { locator.get::<#ty>() },
+    };
+
+    if options.default {
+        Ok(quote! { #get.unwrap_or_default() })
+    } else {
+        Ok(quote! { #get.ok_or_else(|| locator.not_found::<#ty>())? })
+    }
+}
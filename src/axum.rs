@@ -0,0 +1,68 @@
+use crate::{Locator, LocatorError};
+use ::axum::{
+    extract::FromRequestParts,
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+};
+use std::{
+    fmt::{Display, Formatter},
+    sync::Arc,
+};
+
+/// An extractor that resolves a value of type `T` from the `Arc<Locator>` in the request extensions.
+///
+/// The locator can be added using `Extension(Arc::new(locator))` or the `LocatorLayer`.
+///
+/// # Example
+/// ```rust,ignore
+/// async fn get_users(Inject(repo): Inject<Box<dyn UserRepository + Send + Sync>>) -> Response {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Inject<T>(pub T);
+
+impl<T, S> FromRequestParts<S> for Inject<T>
+where
+    T: Send + Sync + 'static,
+    S: Send + Sync,
+{
+    type Rejection = InjectRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let locator = parts
+            .extensions
+            .get::<Arc<Locator>>()
+            .ok_or_else(LocatorError::not_found::<Arc<Locator>>)?;
+
+        let value = locator
+            .get::<T>()
+            .ok_or_else(LocatorError::not_found::<T>)?;
+
+        Ok(Inject(value))
+    }
+}
+
+/// The rejection of the `Inject` extractor, responds with a `500 Internal Server Error`.
+#[derive(Debug)]
+pub struct InjectRejection(pub LocatorError);
+
+impl From<LocatorError> for InjectRejection {
+    fn from(err: LocatorError) -> Self {
+        InjectRejection(err)
+    }
+}
+
+impl Display for InjectRejection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for InjectRejection {}
+
+impl IntoResponse for InjectRejection {
+    fn into_response(self) -> Response {
+        (StatusCode::INTERNAL_SERVER_ERROR, self.0.to_string()).into_response()
+    }
+}
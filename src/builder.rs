@@ -0,0 +1,174 @@
+use crate::{try_locator::TryLocator, Locator, LocatorError, Module};
+use std::{collections::hash_map::RandomState, future::Future, hash::BuildHasher};
+
+/// A builder to create a `Locator` checking that all its services can be resolved.
+///
+/// ```
+/// use kizuna::Locator;
+///
+/// let locator = Locator::builder()
+///     .insert(42_i32)
+///     .insert_with(|locator| format!("value: {}", locator.get::<i32>().unwrap()))
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(locator.get::<String>().unwrap(), "value: 42");
+/// ```
+#[derive(Debug, Default)]
+pub struct LocatorBuilder<S = RandomState> {
+    locator: Locator<S>,
+}
+
+impl<S: BuildHasher + Default> LocatorBuilder<S> {
+    /// Creates a new `LocatorBuilder`.
+    pub fn new() -> Self {
+        LocatorBuilder {
+            locator: Locator::default(),
+        }
+    }
+}
+
+impl<S: BuildHasher> LocatorBuilder<S> {
+    /// Inserts a value of type `T`, see `Locator::insert`.
+    pub fn insert<T>(mut self, value: T) -> Self
+    where
+        T: Send + Sync + Clone + 'static,
+    {
+        self.locator.insert(value);
+        self
+    }
+
+    /// Inserts a value of type `T` using a factory, see `Locator::insert_with`.
+    pub fn insert_with<F, T>(mut self, factory: F) -> Self
+    where
+        F: Fn(&Locator<S>) -> T + 'static + Send + Sync,
+        T: Send + Sync + 'static,
+    {
+        self.locator.insert_with(factory);
+        self
+    }
+
+    /// Inserts a value of type `T` using an async factory, see `Locator::insert_async_with`.
+    pub fn insert_async_with<F, Fut, T>(mut self, factory: F) -> Self
+    where
+        F: Fn(&Locator<S>) -> Fut + 'static + Send + Sync,
+        Fut: Future<Output = T> + Send + 'static,
+        T: Send + Sync + 'static,
+    {
+        self.locator.insert_async_with(factory);
+        self
+    }
+
+    /// Inserts a value of type `T` with the given name, see `Locator::insert_named`.
+    pub fn insert_named<T>(mut self, name: &'static str, value: T) -> Self
+    where
+        T: Send + Sync + Clone + 'static,
+    {
+        self.locator.insert_named(name, value);
+        self
+    }
+
+    /// Registers the services of the given module, see `Locator::install`.
+    pub fn install<M>(mut self, module: M) -> Self
+    where
+        M: Module<S>,
+    {
+        self.locator.install(module);
+        self
+    }
+
+    /// Returns the locator after checking that all its services can be resolved, see `Locator::verify`.
+    pub fn build(self) -> Result<Locator<S>, Vec<LocatorError>> {
+        self.locator.verify()?;
+        Ok(self.locator)
+    }
+
+    /// Returns the locator without checking its services,
+    /// useful when some factories are expected to fail lazily.
+    pub fn build_unchecked(self) -> Locator<S> {
+        self.locator
+    }
+}
+
+impl<S: BuildHasher + Send + Sync> LocatorBuilder<S> {
+    /// Inserts a value of type `T` that may fail to resolve, see `TryLocator::try_insert_with`.
+    pub fn try_insert_with<F, T>(mut self, factory: F) -> Self
+    where
+        F: Fn(&Locator<S>) -> Result<T, LocatorError> + 'static + Send + Sync,
+        T: Send + Sync + Clone + 'static,
+    {
+        self.locator.try_insert_with(factory);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{try_locator::TryLocator, Locator, LocatorError};
+    use std::any::type_name;
+
+    #[derive(Clone)]
+    struct Database;
+
+    #[derive(Clone)]
+    struct Repository {
+        #[allow(dead_code)]
+        db: Database,
+    }
+
+    impl From<Repository> for Database {
+        fn from(_: Repository) -> Self {
+            Database
+        }
+    }
+
+    #[test]
+    fn test_build() {
+        let locator = Locator::builder()
+            .insert(Database)
+            .try_insert_with(|locator| locator.invoke(|db: Database| Repository { db }))
+            .build()
+            .unwrap();
+
+        assert!(locator.try_get::<Repository>().is_ok());
+    }
+
+    #[test]
+    fn test_build_missing_dependency() {
+        let errors = Locator::builder()
+            .try_insert_with(|locator| locator.invoke(|db: Database| Repository { db }))
+            .build()
+            .unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            LocatorError::NotFound { expected, .. } if *expected == type_name::<Database>()
+        ));
+    }
+
+    #[test]
+    fn test_build_cyclic() {
+        let errors = Locator::builder()
+            .insert_with(|locator| Database::from(locator.get::<Repository>().unwrap()))
+            .insert_with(|locator| Repository {
+                db: locator.get::<Database>().unwrap(),
+            })
+            .build()
+            .unwrap_err();
+
+        assert_eq!(errors.len(), 2);
+        assert!(errors
+            .iter()
+            .all(|err| matches!(err, LocatorError::Cyclic { .. })));
+    }
+
+    #[test]
+    fn test_build_unchecked() {
+        let locator = Locator::builder()
+            .try_insert_with(|locator| locator.invoke(|db: Database| Repository { db }))
+            .build_unchecked();
+
+        assert!(locator.try_get::<Repository>().is_err());
+    }
+}
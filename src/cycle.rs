@@ -0,0 +1,139 @@
+//! Tracks the services being resolved in the current thread, to detect the services
+//! that require themselves and to report the chain of services that failed to resolve.
+
+use crate::LocatorError;
+use std::{
+    any::TypeId,
+    cell::RefCell,
+    panic::{self, AssertUnwindSafe},
+};
+
+thread_local! {
+    static RESOLVING: RefCell<Vec<Frame>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Identifies a service being resolved.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Id {
+    /// A service inserted by `try_insert_with`.
+    Type(TypeId),
+    /// The provider of a service that cannot fail, identified by its address.
+    Provider(usize),
+}
+
+struct Frame {
+    id: Id,
+    name: &'static str,
+    fallible: bool,
+}
+
+/// Unwinds from a cyclic dependency to the frame of the service that started the cycle,
+/// used when the cycle contains a factory that cannot return the error.
+struct Unwind {
+    id: Id,
+    err: LocatorError,
+}
+
+/// Runs `f` marking `T` as being resolved in the current thread,
+/// fails with `LocatorError::Cyclic` if `T` is already being resolved.
+pub(crate) fn guard<T, R>(f: impl FnOnce() -> Result<R, LocatorError>) -> Result<R, LocatorError>
+where
+    T: 'static,
+{
+    enter(
+        Id::Type(TypeId::of::<T>()),
+        std::any::type_name::<T>(),
+        true,
+        f,
+    )
+}
+
+/// Runs the factory of the provider at the given address marking it as being resolved in the current thread,
+/// fails with `LocatorError::Cyclic` if the provider is already being resolved.
+pub(crate) fn guard_provider<R>(
+    addr: usize,
+    name: &'static str,
+    f: impl FnOnce() -> R,
+) -> Result<R, LocatorError> {
+    enter(Id::Provider(addr), name, false, || Ok(f()))
+}
+
+fn enter<R>(
+    id: Id,
+    name: &'static str,
+    fallible: bool,
+    f: impl FnOnce() -> Result<R, LocatorError>,
+) -> Result<R, LocatorError> {
+    // Removes the service from the stack even if the closure panics.
+    struct Pop;
+
+    impl Drop for Pop {
+        fn drop(&mut self) {
+            RESOLVING.with(|stack| stack.borrow_mut().pop());
+        }
+    }
+
+    let cycle = RESOLVING.with(|stack| {
+        let mut stack = stack.borrow_mut();
+        match stack.iter().position(|frame| frame.id == id) {
+            Some(start) => {
+                let frames = &stack[start..];
+                let mut chain = frames.iter().map(|frame| frame.name).collect::<Vec<_>>();
+                chain.push(name);
+
+                let recoverable = fallible && frames.iter().all(|frame| frame.fallible);
+                Some((recoverable, LocatorError::Cyclic { chain }))
+            }
+            None => {
+                stack.push(Frame { id, name, fallible });
+                None
+            }
+        }
+    });
+
+    match cycle {
+        Some((true, err)) => return Err(err),
+        // A factory in the cycle cannot return the error, so the factories in the cycle are
+        // abandoned and the service that started it fails instead. Unlike `panic!`,
+        // `resume_unwind` doesn't call the panic hook.
+        Some((false, err)) => panic::resume_unwind(Box::new(Unwind { id, err })),
+        None => {}
+    }
+
+    let _pop = Pop;
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => result,
+        Err(payload) => match payload.downcast::<Unwind>() {
+            Ok(unwind) if unwind.id == id => Err(unwind.err),
+            Ok(unwind) => panic::resume_unwind(unwind),
+            Err(payload) => panic::resume_unwind(payload),
+        },
+    }
+}
+
+/// Wraps the error of a dependency in `LocatorError::WhileResolving` with the service inserted by
+/// `try_insert_with` being resolved in the current thread, if any.
+///
+/// Cyclic errors are returned as is because their chain already contains the services being resolved.
+pub(crate) fn while_resolving(err: LocatorError) -> LocatorError {
+    if matches!(err, LocatorError::Cyclic { .. }) {
+        return err;
+    }
+
+    let target = RESOLVING.with(|stack| {
+        let stack = stack.borrow();
+        stack
+            .iter()
+            .rev()
+            .find(|frame| frame.fallible)
+            .map(|frame| frame.name)
+    });
+
+    match target {
+        Some(target) => LocatorError::WhileResolving {
+            target,
+            cause: Box::new(err),
+        },
+        None => err,
+    }
+}
@@ -0,0 +1,127 @@
+use crate::{
+    locator::{Entry, Key},
+    Locator, Provider, Singleton,
+};
+use std::{collections::hash_map, hash::BuildHasher, marker::PhantomData, sync::Arc};
+
+/// A view into the registration of a service of type `T` in a `Locator`, see `Locator::entry`.
+pub enum ServiceEntry<'a, T, S> {
+    /// The service is registered in the locator.
+    Occupied(OccupiedEntry<'a, T, S>),
+
+    /// The service is not registered in the locator.
+    Vacant(VacantEntry<'a, T, S>),
+}
+
+/// A view into a registered service of type `T`.
+pub struct OccupiedEntry<'a, T, S> {
+    pub(crate) inner: hash_map::OccupiedEntry<'a, Key, Entry<S>>,
+    pub(crate) _marker: PhantomData<fn() -> T>,
+}
+
+/// A view into a service of type `T` that is not registered.
+pub struct VacantEntry<'a, T, S> {
+    pub(crate) inner: hash_map::VacantEntry<'a, Key, Entry<S>>,
+    pub(crate) _marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T, S> ServiceEntry<'a, T, S>
+where
+    T: Send + Sync + 'static,
+    S: BuildHasher,
+{
+    /// Inserts the value if the service is not registered, returns the provider of the service.
+    pub fn or_insert(self, value: T) -> &'a Provider<S>
+    where
+        T: Clone,
+    {
+        match self {
+            ServiceEntry::Occupied(entry) => entry.into_provider(),
+            ServiceEntry::Vacant(entry) => entry.insert(value),
+        }
+    }
+
+    /// Inserts the value returned by the function if the service is not registered,
+    /// returns the provider of the service.
+    pub fn or_insert_with<F>(self, f: F) -> &'a Provider<S>
+    where
+        F: FnOnce() -> T,
+        T: Clone,
+    {
+        match self {
+            ServiceEntry::Occupied(entry) => entry.into_provider(),
+            ServiceEntry::Vacant(entry) => entry.insert(f()),
+        }
+    }
+
+    /// Inserts the factory if the service is not registered, returns the provider of the service.
+    pub fn or_insert_factory<F>(self, factory: F) -> &'a Provider<S>
+    where
+        F: Fn(&Locator<S>) -> T + Send + Sync + 'static,
+    {
+        match self {
+            ServiceEntry::Occupied(entry) => entry.into_provider(),
+            ServiceEntry::Vacant(entry) => entry.insert_with(factory),
+        }
+    }
+}
+
+impl<'a, T, S> OccupiedEntry<'a, T, S>
+where
+    T: Send + Sync + 'static,
+    S: BuildHasher,
+{
+    /// Returns the provider of the service.
+    pub fn provider(&self) -> &Provider<S> {
+        &self.inner.get().provider
+    }
+
+    /// Converts the entry into the provider of the service.
+    pub fn into_provider(self) -> &'a Provider<S> {
+        &self.inner.into_mut().provider
+    }
+
+    /// Replaces the service with the given value, returns the previous provider.
+    pub fn insert(&mut self, value: T) -> Provider<S>
+    where
+        T: Clone,
+    {
+        self.inner.insert(single::<T, S>(value)).provider
+    }
+
+    /// Removes the service from the locator, returns its provider.
+    pub fn remove(self) -> Provider<S> {
+        self.inner.remove().provider
+    }
+}
+
+impl<'a, T, S> VacantEntry<'a, T, S>
+where
+    T: Send + Sync + 'static,
+    S: BuildHasher,
+{
+    /// Registers the given value, returns the provider of the service.
+    pub fn insert(self, value: T) -> &'a Provider<S>
+    where
+        T: Clone,
+    {
+        &self.inner.insert(single::<T, S>(value)).provider
+    }
+
+    /// Registers the given factory, returns the provider of the service.
+    pub fn insert_with<F>(self, factory: F) -> &'a Provider<S>
+    where
+        F: Fn(&Locator<S>) -> T + Send + Sync + 'static,
+    {
+        let provider = Provider::Factory(Arc::new(move |locator| Box::new(factory(locator))));
+        &self.inner.insert(Entry::typed::<T>(provider)).provider
+    }
+}
+
+fn single<T, S>(value: T) -> Entry<S>
+where
+    T: Send + Sync + Clone + 'static,
+    S: BuildHasher,
+{
+    Entry::typed::<T>(Provider::Single(Singleton::from_value(value)))
+}
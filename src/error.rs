@@ -1,10 +1,63 @@
-use std::fmt::{Display, Formatter};
+use std::{
+    fmt::{Display, Formatter},
+    time::Duration,
+};
 
 /// An error that occurred while resolving a dependency.
+///
+/// More variants may be added, so matches must include a wildcard arm.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum LocatorError {
     /// When a dependency is not found.
-    NotFound { expected: &'static str },
+    ///
+    /// `suggestions` contains the names of registered services similar to the expected one.
+    /// Use `LocatorError::not_found` to create it, more fields may be added.
+    #[non_exhaustive]
+    NotFound {
+        expected: &'static str,
+        suggestions: Vec<&'static str>,
+    },
+
+    /// When some of the dependencies of an invoked function are not found, see `Locator::invoke_checked`.
+    ///
+    /// `types` contains the names of all the missing dependencies.
+    Missing { types: Vec<&'static str> },
+
+    /// When a dependency is already registered in the locator.
+    AlreadyRegistered { type_name: &'static str },
+
+    /// When a dependency requires itself to be resolved.
+    ///
+    /// `chain` contains the services being resolved, starting and ending with the same service.
+    Cyclic { chain: Vec<&'static str> },
+
+    /// When the provider of a dependency returns a value of other type.
+    DowncastMismatch {
+        expected: &'static str,
+        found: &'static str,
+    },
+
+    /// When a dependency took longer than expected to be resolved.
+    Timeout {
+        expected: &'static str,
+        elapsed: Duration,
+    },
+
+    /// When a dependency of a service failed to resolve.
+    ///
+    /// `target` is the service that requested the dependency and `cause` is the error of the dependency,
+    /// which is other `WhileResolving` if the error happened deeper in the chain, see `LocatorError::root_cause`.
+    ///
+    /// Only the `target` is displayed, the `cause` is returned by `Error::source`.
+    WhileResolving {
+        target: &'static str,
+        cause: Box<LocatorError>,
+    },
+
+    /// When a `SharedLocator` is modified by a factory while resolving a service from it,
+    /// which would otherwise deadlock.
+    ReentrantMutation,
 
     /// Other error that occurred while resolving a dependency.
     Other(Box<dyn std::error::Error + Send + Sync + 'static>),
@@ -15,6 +68,54 @@ impl LocatorError {
     pub fn not_found<T>() -> LocatorError {
         LocatorError::NotFound {
             expected: std::any::type_name::<T>(),
+            suggestions: Vec::new(),
+        }
+    }
+
+    /// Wraps the given error into `LocatorError::Other`.
+    ///
+    /// A blanket `From<E: Error>` is not possible because `LocatorError` is also an error,
+    /// use this with `map_err` or `ResultExt::or_locator_err` to propagate any error with `?`.
+    pub fn other<E>(err: E) -> LocatorError
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+    {
+        LocatorError::Other(err.into())
+    }
+
+    /// Returns the error that caused a `WhileResolving` error, or this error for other variants.
+    pub fn root_cause(&self) -> &LocatorError {
+        let mut err = self;
+        while let LocatorError::WhileResolving { cause, .. } = err {
+            err = cause;
+        }
+
+        err
+    }
+
+    /// Attempts to downcast the error stored in `Other` to the concrete type `E`,
+    /// returns the original error if is not an `Other` of type `E`.
+    pub fn downcast_other<E>(self) -> Result<E, LocatorError>
+    where
+        E: std::error::Error + 'static,
+    {
+        match self {
+            LocatorError::Other(err) => match err.downcast::<E>() {
+                Ok(err) => Ok(*err),
+                Err(err) => Err(LocatorError::Other(err)),
+            },
+            err => Err(err),
+        }
+    }
+
+    /// Returns a reference to the error stored in `Other` if is of type `E`.
+    pub fn downcast_other_ref<E>(&self) -> Option<&E>
+    where
+        E: std::error::Error + 'static,
+    {
+        match self {
+            LocatorError::Other(err) => err.downcast_ref::<E>(),
+            _ => None,
         }
     }
 }
@@ -22,18 +123,365 @@ impl LocatorError {
 impl Display for LocatorError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            LocatorError::NotFound { expected } => {
-                write!(f, "unable to find `{}` in locator", expected)
+            LocatorError::NotFound {
+                expected,
+                suggestions,
+            } => {
+                write!(f, "unable to find `{}` in locator", expected)?;
+
+                if let Some((first, rest)) = suggestions.split_first() {
+                    write!(f, ", did you mean: `{}`", first)?;
+                    for name in rest {
+                        write!(f, ", `{}`", name)?;
+                    }
+                    write!(f, "?")?;
+                }
+
+                Ok(())
+            }
+            LocatorError::Missing { types } => {
+                write!(f, "unable to find ")?;
+                for (i, name) in types.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "`{}`", name)?;
+                }
+
+                write!(f, " in locator")
+            }
+            LocatorError::AlreadyRegistered { type_name } => {
+                write!(f, "`{}` is already registered in locator", type_name)
+            }
+            LocatorError::Cyclic { chain } => {
+                write!(f, "cyclic dependency detected: ")?;
+                for (i, name) in chain.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " -> ")?;
+                    }
+                    write!(f, "`{}`", name)?;
+                }
+
+                Ok(())
+            }
+            LocatorError::DowncastMismatch { expected, found } => {
+                write!(
+                    f,
+                    "expected `{}` but the provider returned `{}`",
+                    expected, found
+                )
+            }
+            LocatorError::Timeout { expected, elapsed } => {
+                write!(f, "timed out resolving `{}` after {:?}", expected, elapsed)
+            }
+            LocatorError::WhileResolving { target, .. } => {
+                write!(f, "while resolving `{}`", target)
+            }
+            LocatorError::ReentrantMutation => {
+                write!(
+                    f,
+                    "unable to modify the locator while resolving a service from it"
+                )
             }
             LocatorError::Other(err) => err.fmt(f),
         }
     }
 }
 
-impl std::error::Error for LocatorError {}
+impl std::error::Error for LocatorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LocatorError::WhileResolving { cause, .. } => Some(cause.as_ref()),
+            // `Other` is displayed as the wrapped error, so its source is the source of that error.
+            LocatorError::Other(err) => err.source(),
+            _ => None,
+        }
+    }
+}
 
 impl From<Box<dyn std::error::Error + Send + Sync + 'static>> for LocatorError {
     fn from(err: Box<dyn std::error::Error + Send + Sync + 'static>) -> Self {
         LocatorError::Other(err)
     }
 }
+
+/// Converts the error of a `Result` into `LocatorError::Other` to propagate it with `?`,
+/// for example `std::fs::read_to_string(path).or_locator_err()?` in a `try_insert_with` factory.
+pub trait ResultExt<T> {
+    /// Wraps the error into `LocatorError::Other`, see `LocatorError::other`.
+    fn or_locator_err(self) -> Result<T, LocatorError>;
+}
+
+impl<T, E> ResultExt<T> for Result<T, E>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+{
+    fn or_locator_err(self) -> Result<T, LocatorError> {
+        self.map_err(LocatorError::other)
+    }
+}
+
+/// The max number of suggestions in a not found error.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Returns up to three of the `candidates` that are close to the `expected` type name.
+///
+/// The names are compared without their module paths, so `Box<dyn Repo>` is close to
+/// `alloc::boxed::Box<dyn my_app::Repo + Send + Sync>`.
+pub(crate) fn similar_type_names(
+    expected: &'static str,
+    candidates: impl IntoIterator<Item = &'static str>,
+) -> Vec<&'static str> {
+    let expected_short = short_type_name(expected);
+    let mut similar = candidates
+        .into_iter()
+        .filter_map(|name| {
+            let short = short_type_name(name);
+            let distance = levenshtein(&expected_short, &short);
+            let max_len = expected_short.chars().count().max(short.chars().count());
+            (distance * 2 <= max_len).then_some((distance, name))
+        })
+        .collect::<Vec<_>>();
+
+    similar.sort();
+    similar.dedup_by_key(|(_, name)| *name);
+    similar
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, name)| name)
+        .collect()
+}
+
+/// Removes the module paths of a type name, `alloc::string::String` becomes `String`.
+fn short_type_name(name: &str) -> String {
+    let mut short = String::with_capacity(name.len());
+    let mut segment = String::new();
+
+    for c in name.chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            segment.push(c);
+        } else {
+            short.push_str(segment.rsplit("::").next().unwrap_or_default());
+            segment.clear();
+            short.push(c);
+        }
+    }
+
+    short.push_str(segment.rsplit("::").next().unwrap_or_default());
+    short
+}
+
+/// The number of single char edits required to change `a` into `b`.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { prev } else { prev + 1 };
+            prev = row[j + 1];
+            row[j + 1] = cost.min(row[j] + 1).min(prev + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum RepositoryError {
+        ConnectionLost,
+    }
+
+    impl Display for RepositoryError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "connection lost")
+        }
+    }
+
+    impl std::error::Error for RepositoryError {}
+
+    #[test]
+    fn test_downcast_other() {
+        let err = LocatorError::Other(Box::new(RepositoryError::ConnectionLost));
+        assert_eq!(
+            err.downcast_other_ref::<RepositoryError>(),
+            Some(&RepositoryError::ConnectionLost)
+        );
+        assert!(err.downcast_other_ref::<std::fmt::Error>().is_none());
+
+        let err = err.downcast_other::<std::fmt::Error>().unwrap_err();
+        assert_eq!(
+            err.downcast_other::<RepositoryError>().unwrap(),
+            RepositoryError::ConnectionLost
+        );
+
+        let err = LocatorError::not_found::<String>();
+        assert!(matches!(
+            err.downcast_other::<RepositoryError>(),
+            Err(LocatorError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("abc", "abc"), 0);
+    }
+
+    #[test]
+    fn test_not_found_display_with_suggestions() {
+        let err = LocatorError::NotFound {
+            expected: "Box<dyn Repo>",
+            suggestions: vec!["Box<dyn Repo + Send>", "Box<dyn Repos>"],
+        };
+
+        assert_eq!(
+            err.to_string(),
+            "unable to find `Box<dyn Repo>` in locator, did you mean: `Box<dyn Repo + Send>`, `Box<dyn Repos>`?"
+        );
+    }
+
+    #[test]
+    fn test_while_resolving_display() {
+        let err = LocatorError::WhileResolving {
+            target: "Service",
+            cause: Box::new(LocatorError::WhileResolving {
+                target: "Box<dyn UserRepository>",
+                cause: Box::new(LocatorError::not_found::<String>()),
+            }),
+        };
+
+        assert_eq!(err.to_string(), "while resolving `Service`");
+        assert_eq!(
+            err.root_cause().to_string(),
+            "unable to find `alloc::string::String` in locator"
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let cases = [
+            (
+                LocatorError::not_found::<String>(),
+                "unable to find `alloc::string::String` in locator",
+            ),
+            (
+                LocatorError::Missing {
+                    types: vec!["Config", "Pool"],
+                },
+                "unable to find `Config`, `Pool` in locator",
+            ),
+            (
+                LocatorError::AlreadyRegistered {
+                    type_name: "Config",
+                },
+                "`Config` is already registered in locator",
+            ),
+            (
+                LocatorError::Cyclic {
+                    chain: vec!["A", "B", "A"],
+                },
+                "cyclic dependency detected: `A` -> `B` -> `A`",
+            ),
+            (
+                LocatorError::DowncastMismatch {
+                    expected: "String",
+                    found: "i32",
+                },
+                "expected `String` but the provider returned `i32`",
+            ),
+            (
+                LocatorError::Timeout {
+                    expected: "Pool",
+                    elapsed: Duration::from_millis(500),
+                },
+                "timed out resolving `Pool` after 500ms",
+            ),
+            (
+                LocatorError::WhileResolving {
+                    target: "Repository",
+                    cause: Box::new(LocatorError::not_found::<String>()),
+                },
+                "while resolving `Repository`",
+            ),
+            (
+                LocatorError::ReentrantMutation,
+                "unable to modify the locator while resolving a service from it",
+            ),
+            (LocatorError::other("connection lost"), "connection lost"),
+        ];
+
+        for (err, expected) in cases {
+            assert_eq!(err.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_source() {
+        use std::error::Error;
+
+        let err = LocatorError::WhileResolving {
+            target: "Service",
+            cause: Box::new(LocatorError::WhileResolving {
+                target: "Repository",
+                cause: Box::new(LocatorError::Timeout {
+                    expected: "Pool",
+                    elapsed: Duration::from_secs(1),
+                }),
+            }),
+        };
+
+        let mut chain = Vec::new();
+        let mut current: Option<&dyn Error> = Some(&err);
+        while let Some(err) = current {
+            chain.push(err.to_string());
+            current = err.source();
+        }
+
+        assert_eq!(
+            chain,
+            vec![
+                "while resolving `Service`",
+                "while resolving `Repository`",
+                "timed out resolving `Pool` after 1s",
+            ]
+        );
+
+        assert!(LocatorError::not_found::<String>().source().is_none());
+        assert!(LocatorError::Cyclic { chain: vec![] }.source().is_none());
+        assert!(LocatorError::other(RepositoryError::ConnectionLost)
+            .source()
+            .is_none());
+
+        // `Other` is transparent, its source is the source of the wrapped error.
+        #[derive(Debug)]
+        struct ConfigError(std::num::ParseIntError);
+
+        impl Display for ConfigError {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                write!(f, "invalid config")
+            }
+        }
+
+        impl Error for ConfigError {
+            fn source(&self) -> Option<&(dyn Error + 'static)> {
+                Some(&self.0)
+            }
+        }
+
+        let err = LocatorError::other(ConfigError("port".parse::<u16>().unwrap_err()));
+        assert_eq!(err.to_string(), "invalid config");
+        assert_eq!(
+            err.source().unwrap().to_string(),
+            "invalid digit found in string"
+        );
+    }
+}
@@ -1,23 +1,121 @@
-use crate::{LocatorError, Locator};
+use crate::{try_locator, LocatorError, Locator};
+use std::{
+    collections::hash_map::RandomState,
+    hash::BuildHasher,
+    ops::Deref,
+    sync::Arc,
+};
 
 /// A type that can be constructed from a `Locator`.
-pub trait FromLocator : Sized {
+///
+/// The type parameter is the hasher of the locator, see `Locator<S>`.
+/// Implemented for tuples of up to 16 services.
+pub trait FromLocator<S = RandomState>: Sized {
     /// Constructs this type from the given `Locator`.
-    fn from_locator(locator: &Locator) -> Result<Self, LocatorError>;
+    fn from_locator(locator: &Locator<S>) -> Result<Self, LocatorError>;
+
+    /// Returns `true` if this type can be constructed from the given `Locator`, see `Locator::contains_all`.
+    ///
+    /// The default implementation constructs the value, the tuples only check that their services exist.
+    fn contained_in(locator: &Locator<S>) -> bool {
+        Self::from_locator(locator).is_ok()
+    }
+
+    /// Returns the names of the services required by this type that are not in the given `Locator`,
+    /// see `Locator::invoke_checked`.
+    ///
+    /// The default implementation returns the name of this type if is not `contained_in` the locator.
+    fn missing_in(locator: &Locator<S>) -> Vec<&'static str> {
+        if Self::contained_in(locator) {
+            Vec::new()
+        } else {
+            vec![std::any::type_name::<Self>()]
+        }
+    }
+
+    /// Constructs this type from the given `Locator` also resolving the services inserted
+    /// with `TryLocator::try_insert_with`, see `Locator::try_invoke`.
+    ///
+    /// The default implementation calls `from_locator`, the tuples resolve each service using `try_get`.
+    fn try_from_locator(locator: &Locator<S>) -> Result<Self, LocatorError> {
+        Self::from_locator(locator)
+    }
+}
+
+/// Resolves a service inserted with `Locator::insert_singleton`, the value is shared and never cloned.
+///
+/// Arguments of an invoked function of type `Arc<T>` resolve to the same services
+/// because those services are stored under that type.
+impl<T, S> FromLocator<S> for Arc<T>
+where
+    T: Send + Sync + 'static,
+    S: BuildHasher,
+{
+    fn from_locator(locator: &Locator<S>) -> Result<Self, LocatorError> {
+        locator
+            .get_arc::<T>()
+            .ok_or_else(|| locator.not_found::<Arc<T>>())
+    }
+}
+
+/// Resolves an optional service, `None` if there is no service of type `T`.
+///
+/// To receive an optional argument in an invoked function use `Locator::invoke_ref`,
+/// the arguments of `invoke` are resolved by their own type.
+impl<T, S> FromLocator<S> for Option<T>
+where
+    T: Send + Sync + 'static,
+    S: BuildHasher,
+{
+    fn from_locator(locator: &Locator<S>) -> Result<Self, LocatorError> {
+        Ok(locator.get::<T>())
+    }
+}
+
+/// Resolves a service using `TryLocator::try_get`, the error is returned as the value.
+///
+/// Arguments of an invoked function of type `Result<T, LocatorError>` resolve to the services
+/// inserted with `try_insert_with` because those services are stored under that type.
+impl<T, S> FromLocator<S> for Result<T, LocatorError>
+where
+    T: Send + Sync + 'static,
+    S: BuildHasher,
+{
+    fn from_locator(locator: &Locator<S>) -> Result<Self, LocatorError> {
+        Ok(try_locator::try_resolve::<T, S>(locator))
+    }
 }
 
 macro_rules! impl_from_locator_for_tuple {
     ( $($ty:ident),* ) => {
-        impl<$($ty),*> FromLocator for ($($ty,)*) 
-            where $($ty: Send + Sync + 'static),* {
+        impl<S, $($ty),*> FromLocator<S> for ($($ty,)*) 
+            where S: BuildHasher + 'static, $($ty: Send + Sync + 'static),* {
 
-            fn from_locator(locator: &Locator) -> Result<Self, LocatorError> {
+            fn from_locator(locator: &Locator<S>) -> Result<Self, LocatorError> {
                 Ok((
                     $(
-                        locator.get::<$ty>().ok_or(LocatorError::NotFound { expected: std::any::type_name::<$ty>() })?
+                        locator.resolve_direct::<$ty>()?
                     ,)*
                 ))
             }
+
+            fn contained_in(locator: &Locator<S>) -> bool {
+                $(locator.contains::<$ty>())&&*
+            }
+
+            fn try_from_locator(locator: &Locator<S>) -> Result<Self, LocatorError> {
+                Ok(($(try_locator::try_resolve::<$ty, S>(locator)?,)*))
+            }
+
+            fn missing_in(locator: &Locator<S>) -> Vec<&'static str> {
+                let mut missing = Vec::new();
+                $(
+                    if !locator.contains::<$ty>() {
+                        missing.push(std::any::type_name::<$ty>());
+                    }
+                )*
+                missing
+            }
         }
     };
 }
@@ -34,4 +132,214 @@ impl_from_locator_for_tuple!(A, B, C, D, E, F, G, H, I);
 impl_from_locator_for_tuple!(A, B, C, D, E, F, G, H, I, J);
 impl_from_locator_for_tuple!(A, B, C, D, E, F, G, H, I, J, K);
 impl_from_locator_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
+impl_from_locator_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M);
+impl_from_locator_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N);
+impl_from_locator_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O);
+impl_from_locator_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
 
+/// A type that can be constructed borrowing the services of a `Locator`, see `Locator::invoke_ref`.
+///
+/// Implemented for references to services, optional services, `LocatorRef` and tuples of up to 16 of those.
+pub trait FromLocatorRef<'a, S = RandomState>: Sized {
+    /// Constructs this type borrowing from the given `Locator`.
+    fn from_locator_ref(locator: &'a Locator<S>) -> Result<Self, LocatorError>;
+}
+
+/// Borrows the cached value of `T`, only the services inserted as a value or cached can be borrowed.
+impl<'a, T, S> FromLocatorRef<'a, S> for &'a T
+where
+    T: Send + Sync + 'static,
+    S: BuildHasher,
+{
+    fn from_locator_ref(locator: &'a Locator<S>) -> Result<Self, LocatorError> {
+        match locator.get_ref::<T>() {
+            Some(value) => Ok(value),
+            None if locator.contains::<T>() => Err(LocatorError::other(format!(
+                "unable to borrow `{}`, only services inserted as a value or cached can be borrowed",
+                std::any::type_name::<T>()
+            ))),
+            None => Err(locator.not_found::<T>()),
+        }
+    }
+}
+
+/// Resolves an optional service, `None` if there is no service of type `T`.
+///
+/// The value is owned so any service can be resolved, for example `locator.invoke_ref(|config: Option<Config>| ...)`.
+impl<'a, T, S> FromLocatorRef<'a, S> for Option<T>
+where
+    T: Send + Sync + 'static,
+    S: BuildHasher,
+{
+    fn from_locator_ref(locator: &'a Locator<S>) -> Result<Self, LocatorError> {
+        Ok(locator.get::<T>())
+    }
+}
+
+/// The locator that invokes a function with `invoke_ref`, an argument of this type receives the locator itself
+/// so the function can resolve other services, for example `locator.invoke_ref(|l: LocatorRef, config: &Config| ...)`.
+///
+/// The locator is borrowed, so the services resolved through it are the same as resolving them from the locator directly.
+pub struct LocatorRef<'a, S = RandomState>(&'a Locator<S>);
+
+impl<S> Deref for LocatorRef<'_, S> {
+    type Target = Locator<S>;
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+impl<'a, S> FromLocatorRef<'a, S> for LocatorRef<'a, S> {
+    fn from_locator_ref(locator: &'a Locator<S>) -> Result<Self, LocatorError> {
+        Ok(LocatorRef(locator))
+    }
+}
+
+macro_rules! impl_from_locator_ref_for_tuple {
+    ( $($ty:ident),* ) => {
+        impl<'a, S, $($ty),*> FromLocatorRef<'a, S> for ($($ty,)*)
+            where $($ty: FromLocatorRef<'a, S>),* {
+            fn from_locator_ref(locator: &'a Locator<S>) -> Result<Self, LocatorError> {
+                Ok(($($ty::from_locator_ref(locator)?,)*))
+            }
+        }
+    };
+}
+
+impl_from_locator_ref_for_tuple!(A);
+impl_from_locator_ref_for_tuple!(A, B);
+impl_from_locator_ref_for_tuple!(A, B, C);
+impl_from_locator_ref_for_tuple!(A, B, C, D);
+impl_from_locator_ref_for_tuple!(A, B, C, D, E);
+impl_from_locator_ref_for_tuple!(A, B, C, D, E, F);
+impl_from_locator_ref_for_tuple!(A, B, C, D, E, F, G);
+impl_from_locator_ref_for_tuple!(A, B, C, D, E, F, G, H);
+impl_from_locator_ref_for_tuple!(A, B, C, D, E, F, G, H, I);
+impl_from_locator_ref_for_tuple!(A, B, C, D, E, F, G, H, I, J);
+impl_from_locator_ref_for_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+impl_from_locator_ref_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
+impl_from_locator_ref_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M);
+impl_from_locator_ref_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N);
+impl_from_locator_ref_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O);
+impl_from_locator_ref_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
+
+#[cfg(test)]
+mod tests {
+    use crate::{try_locator::TryLocator, FromLocator, Locator, LocatorError};
+    use std::sync::Arc;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Config(i32);
+
+    #[test]
+    fn test_arc_from_locator() {
+        let mut locator = Locator::new();
+        assert!(Arc::<Config>::from_locator(&locator).is_err());
+
+        locator.insert_singleton(Config(42));
+        let first = Arc::<Config>::from_locator(&locator).unwrap();
+        let strong_count = Arc::strong_count(&first);
+
+        let (second, same) = locator
+            .invoke(|config: Arc<Config>| {
+                let same = Arc::ptr_eq(&first, &config);
+                (config, same)
+            })
+            .unwrap();
+
+        assert!(same);
+        assert_eq!(*second, Config(42));
+        assert_eq!(Arc::strong_count(&first), strong_count + 1);
+    }
+
+    #[test]
+    fn test_missing_in() {
+        let mut locator = Locator::new();
+        locator.insert(Config(42));
+
+        assert!(<(Config,)>::missing_in(&locator).is_empty());
+        assert_eq!(
+            <(String, Config, i32)>::missing_in(&locator),
+            vec![
+                std::any::type_name::<String>(),
+                std::any::type_name::<i32>()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_result_from_locator() {
+        let mut locator = Locator::new();
+        locator.try_insert_with::<_, Config>(|_| Ok(Config(42)));
+        locator.insert(String::from("kizuna"));
+
+        let result = Result::<Config, LocatorError>::from_locator(&locator).unwrap();
+        assert_eq!(result.unwrap(), Config(42));
+
+        let result = Result::<String, LocatorError>::from_locator(&locator).unwrap();
+        assert_eq!(result.unwrap(), "kizuna");
+    }
+
+    #[test]
+    fn test_result_from_locator_of_unregistered_service() {
+        let locator = Locator::new();
+
+        let result = Result::<Config, LocatorError>::from_locator(&locator).unwrap();
+        assert!(matches!(result, Err(LocatorError::NotFound { .. })));
+    }
+
+    #[test]
+    fn test_result_from_locator_of_failing_service() {
+        let mut locator = Locator::new();
+        locator.try_insert_with::<_, Config>(|_| Err(LocatorError::other("invalid config")));
+
+        let result = Result::<Config, LocatorError>::from_locator(&locator).unwrap();
+        assert_eq!(result.unwrap_err().to_string(), "invalid config");
+
+        let result = locator
+            .invoke(|config: Result<Config, LocatorError>| config)
+            .unwrap();
+        assert_eq!(result.unwrap_err().to_string(), "invalid config");
+    }
+
+    #[test]
+    fn test_invoke_with_fallible_service() {
+        #[derive(Debug, Clone)]
+        struct ServiceB;
+
+        let mut locator = Locator::new();
+        locator.try_insert_with::<_, ServiceB>(|_| Err(LocatorError::not_found::<ServiceB>()));
+
+        let is_err = locator
+            .invoke(|service: Result<ServiceB, LocatorError>| service.is_err())
+            .unwrap();
+
+        assert!(is_err);
+    }
+
+    #[test]
+    fn test_option_from_locator() {
+        let mut locator = Locator::new();
+        assert_eq!(Option::<Config>::from_locator(&locator).unwrap(), None);
+
+        locator.insert(Config(42));
+        assert_eq!(
+            Option::<Config>::from_locator(&locator).unwrap(),
+            Some(Config(42))
+        );
+    }
+
+    #[test]
+    fn test_invoke_with_optional_service() {
+        let mut locator = Locator::new();
+        locator.insert_with(|_| Config(42));
+
+        let (config, name) = locator
+            .invoke_ref(|config: Option<Config>, name: Option<String>| (config, name))
+            .unwrap();
+
+        assert_eq!(config, Some(Config(42)));
+        assert_eq!(name, None);
+    }
+}
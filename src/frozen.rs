@@ -0,0 +1,160 @@
+use crate::{Invoke, Locator, LocatorError, Provider};
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt::{Debug, Formatter},
+    hash::BuildHasher,
+    sync::OnceLock,
+};
+
+/// An immutable `Locator` that resolves each service once, see `Locator::into_frozen`.
+///
+/// The value returned by a factory the first time it is resolved is cached and cloned
+/// on each following resolution. The factories still resolve their dependencies from the `Locator`.
+pub struct FrozenLocator<S> {
+    locator: Locator<S>,
+    cache: HashMap<TypeId, OnceLock<Box<dyn Any + Send + Sync>>>,
+}
+
+impl<S: BuildHasher> Locator<S> {
+    /// Converts this locator into a `FrozenLocator` that caches the values created by the factories.
+    pub fn into_frozen(self) -> FrozenLocator<S> {
+        let cache = self
+            .resolvable_keys()
+            .into_iter()
+            .filter(|id| matches!(self.unchecked_get(id), Some(Provider::Factory(_))))
+            .map(|id| (id, OnceLock::new()))
+            .collect();
+
+        FrozenLocator {
+            locator: self,
+            cache,
+        }
+    }
+}
+
+impl<S: BuildHasher> FrozenLocator<S> {
+    /// Returns a value of type `T` if it exists, the factory of the service only runs the first time.
+    pub fn get<T>(&self) -> Option<T>
+    where
+        T: Send + Sync + Clone + 'static,
+    {
+        let provider = self.locator.unchecked_get(&TypeId::of::<T>())?;
+
+        match self.cache.get(&TypeId::of::<T>()) {
+            Some(cached) => cached
+                .get_or_init(|| Box::new(self.locator.resolve_provider::<T>(provider)))
+                .downcast_ref::<Option<T>>()?
+                .clone(),
+            None => self.locator.resolve_provider::<T>(provider),
+        }
+    }
+
+    /// Returns `true` if the locator contains a value of type `T`.
+    pub fn contains<T>(&self) -> bool
+    where
+        T: Send + Sync + 'static,
+    {
+        self.locator.contains::<T>()
+    }
+
+    /// Invoke the given function injecting the dependencies from this locator.
+    pub fn invoke<F, Args>(&self, f: F) -> Result<F::Output, LocatorError>
+    where
+        F: Invoke<Args>,
+        Args: FromFrozenLocator<S>,
+    {
+        let args = Args::from_frozen_locator(self)?;
+        Ok(Invoke::call(f, args))
+    }
+}
+
+impl<S> Debug for FrozenLocator<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("FrozenLocator").field(&self.locator).finish()
+    }
+}
+
+/// A type that can be constructed from a `FrozenLocator`.
+pub trait FromFrozenLocator<S>: Sized {
+    /// Constructs this type from the given `FrozenLocator`.
+    fn from_frozen_locator(locator: &FrozenLocator<S>) -> Result<Self, LocatorError>;
+}
+
+macro_rules! impl_from_frozen_locator_for_tuple {
+    ( $($ty:ident),* ) => {
+        impl<S, $($ty),*> FromFrozenLocator<S> for ($($ty,)*)
+            where S: BuildHasher, $($ty: Send + Sync + Clone + 'static),* {
+            fn from_frozen_locator(locator: &FrozenLocator<S>) -> Result<Self, LocatorError> {
+                Ok((
+                    $(
+                        locator.get::<$ty>().ok_or_else(|| locator.locator.not_found::<$ty>())?
+                    ,)*
+                ))
+            }
+        }
+    };
+}
+
+impl_from_frozen_locator_for_tuple!(A);
+impl_from_frozen_locator_for_tuple!(A, B);
+impl_from_frozen_locator_for_tuple!(A, B, C);
+impl_from_frozen_locator_for_tuple!(A, B, C, D);
+impl_from_frozen_locator_for_tuple!(A, B, C, D, E);
+impl_from_frozen_locator_for_tuple!(A, B, C, D, E, F);
+impl_from_frozen_locator_for_tuple!(A, B, C, D, E, F, G);
+impl_from_frozen_locator_for_tuple!(A, B, C, D, E, F, G, H);
+impl_from_frozen_locator_for_tuple!(A, B, C, D, E, F, G, H, I);
+impl_from_frozen_locator_for_tuple!(A, B, C, D, E, F, G, H, I, J);
+impl_from_frozen_locator_for_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+impl_from_frozen_locator_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
+impl_from_frozen_locator_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M);
+impl_from_frozen_locator_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N);
+impl_from_frozen_locator_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O);
+impl_from_frozen_locator_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
+
+#[cfg(test)]
+mod tests {
+    use crate::{Locator, LocatorError};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn test_factory_runs_once() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut locator = Locator::new();
+        locator.insert(String::from("hello"));
+        locator.insert_with({
+            let calls = calls.clone();
+            move |locator| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                locator.get::<String>().unwrap().len()
+            }
+        });
+
+        let frozen = locator.into_frozen();
+        for _ in 0..100 {
+            assert_eq!(frozen.get::<usize>(), Some(5));
+        }
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(frozen.contains::<String>());
+        assert!(frozen.get::<i32>().is_none());
+    }
+
+    #[test]
+    fn test_invoke() {
+        let mut locator = Locator::new();
+        locator.insert(String::from("hello"));
+        locator.insert_with(|_| 10_usize);
+
+        let frozen = locator.into_frozen();
+        let result = frozen.invoke(|s: String, n: usize| format!("{s} {n}"));
+        assert_eq!(result.unwrap(), "hello 10");
+
+        let err = frozen.invoke(|n: i32| n).unwrap_err();
+        assert!(matches!(err, LocatorError::NotFound { .. }));
+    }
+}
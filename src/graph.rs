@@ -0,0 +1,74 @@
+//! Records the services requested while other services are resolved, see `Locator::to_dot`.
+
+use std::cell::RefCell;
+
+#[derive(Default)]
+struct Tracker {
+    stack: Vec<&'static str>,
+    edges: Vec<(&'static str, &'static str)>,
+}
+
+thread_local! {
+    static TRACKER: RefCell<Option<Tracker>> = const { RefCell::new(None) };
+}
+
+/// Records the dependencies resolved while the closure runs, returns the edges found
+/// from each service to the services it requested.
+pub(crate) fn track(f: impl FnOnce()) -> Vec<(&'static str, &'static str)> {
+    // Restores the previous tracker even if the closure panics.
+    struct Restore(Option<Option<Tracker>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            if let Some(prev) = self.0.take() {
+                TRACKER.with(|t| *t.borrow_mut() = prev);
+            }
+        }
+    }
+
+    let prev = TRACKER.with(|t| t.borrow_mut().replace(Tracker::default()));
+    let restore = Restore(Some(prev));
+    f();
+
+    let tracker = TRACKER.with(|t| t.borrow_mut().take());
+    drop(restore);
+    tracker.map(|tracker| tracker.edges).unwrap_or_default()
+}
+
+/// Marks the given service as being resolved until the returned guard is dropped,
+/// does nothing if the dependencies are not being tracked.
+pub(crate) fn enter(name: &'static str) -> Tracking {
+    let tracking = TRACKER.with(|t| {
+        let mut tracker = t.borrow_mut();
+        let Some(tracker) = tracker.as_mut() else {
+            return false;
+        };
+
+        if let Some(&parent) = tracker.stack.last() {
+            if parent != name {
+                tracker.edges.push((parent, name));
+            }
+        }
+
+        tracker.stack.push(name);
+        true
+    });
+
+    Tracking { tracking }
+}
+
+pub(crate) struct Tracking {
+    tracking: bool,
+}
+
+impl Drop for Tracking {
+    fn drop(&mut self) {
+        if self.tracking {
+            TRACKER.with(|t| {
+                if let Some(tracker) = t.borrow_mut().as_mut() {
+                    tracker.stack.pop();
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,40 @@
+/// How a service creates its values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ProviderKind {
+    /// A value created once and cloned on each resolution, see `Locator::insert`.
+    Single,
+
+    /// A value created by a factory the first time it is resolved, see `Locator::insert_cached_with`.
+    Cached,
+
+    /// A value created by a factory on each resolution, see `Locator::insert_with`.
+    Factory,
+
+    /// A value created by an async factory, see `Locator::insert_async_with`.
+    AsyncFactory,
+}
+
+/// A registered service, see `Locator::info`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ServiceInfo {
+    /// The name of the type of the service.
+    pub type_name: &'static str,
+
+    /// The name of the service if it was inserted with a name.
+    pub name: Option<&'static str>,
+
+    /// How the service creates its values.
+    pub kind: ProviderKind,
+}
+
+/// The services registered in a `Locator`, see `Locator::info`.
+///
+/// Only the type names are recorded, never the values of the services.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LocatorInfo {
+    /// The services sorted by type name.
+    pub services: Vec<ServiceInfo>,
+}
@@ -0,0 +1,36 @@
+use crate::Locator;
+
+#[doc(hidden)]
+pub use ::inventory as __inventory;
+
+/// A service registered using the `#[service]` attribute, see `Locator::from_inventory`.
+pub struct ServiceRegistration {
+    register: fn(&mut Locator),
+}
+
+impl ServiceRegistration {
+    /// Creates a registration that inserts its services using the given function.
+    pub const fn new(register: fn(&mut Locator)) -> Self {
+        ServiceRegistration { register }
+    }
+
+    /// Inserts the services of this registration into the given `Locator`.
+    pub fn register(&self, locator: &mut Locator) {
+        (self.register)(locator)
+    }
+}
+
+::inventory::collect!(ServiceRegistration);
+
+impl Locator {
+    /// Creates a `Locator` with all the services registered using the `#[service]` attribute.
+    pub fn from_inventory() -> Self {
+        let mut locator = Locator::new();
+
+        for registration in ::inventory::iter::<ServiceRegistration> {
+            registration.register(&mut locator);
+        }
+
+        locator
+    }
+}
@@ -1,6 +1,8 @@
 use std::future::Future;
 
 /// Represents a function that can be invoke using a service locator.
+///
+/// Implemented for functions of up to 16 arguments.
 pub trait Invoke<Args> {
     /// The result of the function.
     type Output;
@@ -38,8 +40,14 @@ impl_invoke!(A, B, C, D, E, F, G, H, I);
 impl_invoke!(A, B, C, D, E, F, G, H, I, J);
 impl_invoke!(A, B, C, D, E, F, G, H, I, J, K);
 impl_invoke!(A, B, C, D, E, F, G, H, I, J, K, L);
+impl_invoke!(A, B, C, D, E, F, G, H, I, J, K, L, M);
+impl_invoke!(A, B, C, D, E, F, G, H, I, J, K, L, M, N);
+impl_invoke!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O);
+impl_invoke!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
 
 /// Represents an async function that can be invoke using a service locator.
+///
+/// Implemented for functions of up to 16 arguments.
 pub trait AsyncInvoke<Args> {
     /// The resulting future.
     type Fut: Future;
@@ -78,3 +86,7 @@ impl_async_invoke!(A, B, C, D, E, F, G, H, I);
 impl_async_invoke!(A, B, C, D, E, F, G, H, I, J);
 impl_async_invoke!(A, B, C, D, E, F, G, H, I, J, K);
 impl_async_invoke!(A, B, C, D, E, F, G, H, I, J, K, L);
+impl_async_invoke!(A, B, C, D, E, F, G, H, I, J, K, L, M);
+impl_async_invoke!(A, B, C, D, E, F, G, H, I, J, K, L, M, N);
+impl_async_invoke!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O);
+impl_async_invoke!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
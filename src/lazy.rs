@@ -0,0 +1,50 @@
+use std::{
+    fmt::{Debug, Formatter},
+    sync::{Arc, OnceLock},
+};
+
+/// A value created the first time it is used, all the clones share the same value.
+///
+/// Services inserted using `Locator::insert_lazy` are resolved as `Lazy<T>`, so they can be
+/// used as arguments of `Locator::invoke` and the value is only created if the function calls `get`.
+pub struct Lazy<T>(Arc<Inner<T>>);
+
+struct Inner<T> {
+    init: Box<dyn Fn() -> T + Send + Sync>,
+    value: OnceLock<T>,
+}
+
+impl<T> Lazy<T> {
+    /// Creates a new `Lazy` that runs `init` the first time the value is requested.
+    pub fn new<F>(init: F) -> Self
+    where
+        F: Fn() -> T + Send + Sync + 'static,
+    {
+        Lazy(Arc::new(Inner {
+            init: Box::new(init),
+            value: OnceLock::new(),
+        }))
+    }
+
+    /// Returns a reference to the value, creating it if needed.
+    pub fn get(&self) -> &T {
+        self.0.value.get_or_init(|| (self.0.init)())
+    }
+
+    /// Returns `true` if the value was already created.
+    pub fn is_initialized(&self) -> bool {
+        self.0.value.get().is_some()
+    }
+}
+
+impl<T> Clone for Lazy<T> {
+    fn clone(&self) -> Self {
+        Lazy(self.0.clone())
+    }
+}
+
+impl<T: Debug> Debug for Lazy<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Lazy").field(&self.0.value.get()).finish()
+    }
+}
@@ -3,10 +3,51 @@
 /// Provides a mechanism for insert and get dependencies that may fail.
 pub mod try_locator;
 
+/// Provides a `tower` layer that injects the locator into the requests.
+#[cfg(feature = "tower")]
+pub mod tower;
+
+/// Provides an `axum` extractor that resolves services from the locator of the request.
+#[cfg(feature = "axum")]
+pub mod axum;
+
+/// Provides the automatic registration of services using the `#[service]` attribute.
+#[cfg(feature = "inventory")]
+pub mod inventory;
+
 //
+mod builder;
+mod cycle;
+mod entry;
 mod error;
 mod from_locator;
+mod frozen;
+mod graph;
+mod info;
 mod invoke;
+mod lazy;
+mod local_locator;
 mod locator;
+mod macros;
+mod metrics;
+#[cfg(feature = "test-util")]
+mod mock;
+mod module;
+mod mutable;
+mod retry;
+mod shared;
+mod small_locator;
+mod timer;
+mod trace;
+
+pub use {
+    builder::*, entry::*, error::*, from_locator::*, frozen::*, info::*, invoke::*, lazy::*,
+    local_locator::*, locator::*, metrics::*, module::*, mutable::*, retry::*, shared::*,
+    small_locator::*,
+};
+
+#[cfg(feature = "derive")]
+pub use kizuna_derive::{FromLocator, Inject};
 
-pub use {error::*, from_locator::*, invoke::*, locator::*};
+#[cfg(feature = "inventory")]
+pub use kizuna_derive::service;
@@ -0,0 +1,230 @@
+#![allow(clippy::type_complexity)]
+
+use crate::{Invoke, LocatorError};
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt::{Debug, Formatter},
+    rc::Rc,
+};
+
+/// A provider of a `LocalLocator`, the services are not required to be `Send` or `Sync`.
+#[derive(Clone)]
+enum LocalProvider {
+    Single {
+        value: Rc<dyn Any>,
+        clone: fn(&dyn Any) -> Box<dyn Any>,
+    },
+    Factory(Rc<dyn Fn(&LocalLocator) -> Box<dyn Any>>),
+}
+
+/// A service locator for single-threaded applications.
+///
+/// Unlike `Locator` the services are not required to be `Send + Sync`, so it can hold
+/// services like `Rc<RefCell<T>>`. Because of this a `LocalLocator` is `!Send` and `!Sync`,
+/// it cannot be shared or moved to other threads.
+///
+/// ```compile_fail
+/// fn assert_send<T: Send>() {}
+/// assert_send::<kizuna::LocalLocator>();
+/// ```
+#[derive(Default, Clone)]
+pub struct LocalLocator {
+    providers: HashMap<TypeId, (LocalProvider, &'static str)>,
+}
+
+impl LocalLocator {
+    /// Creates a new `LocalLocator`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Inserts a value of type `T` into the `LocalLocator`.
+    pub fn insert<T>(&mut self, value: T)
+    where
+        T: Clone + 'static,
+    {
+        fn clone_any<T: Clone + 'static>(value: &dyn Any) -> Box<dyn Any> {
+            let value = value
+                .downcast_ref::<T>()
+                .expect("local value type mismatch");
+            Box::new(value.clone())
+        }
+
+        let provider = LocalProvider::Single {
+            value: Rc::new(value),
+            clone: clone_any::<T>,
+        };
+
+        self.providers
+            .insert(TypeId::of::<T>(), (provider, std::any::type_name::<T>()));
+    }
+
+    /// Inserts a value of type `T` into the `LocalLocator` using a factory function that takes a `LocalLocator` as input.
+    pub fn insert_with<F, T>(&mut self, factory: F)
+    where
+        F: Fn(&Self) -> T + 'static,
+        T: 'static,
+    {
+        let provider = LocalProvider::Factory(Rc::new(move |locator| Box::new(factory(locator))));
+
+        self.providers
+            .insert(TypeId::of::<T>(), (provider, std::any::type_name::<T>()));
+    }
+
+    /// Returns a value of type `T` from the `LocalLocator` if it exists.
+    pub fn get<T>(&self) -> Option<T>
+    where
+        T: 'static,
+    {
+        let (provider, _) = self.providers.get(&TypeId::of::<T>())?;
+        let value = match provider {
+            LocalProvider::Single { value, clone } => clone(value.as_ref()),
+            LocalProvider::Factory(f) => f(self),
+        };
+
+        value.downcast::<T>().map(|x| *x).ok()
+    }
+
+    /// Returns a reference to the value of type `T` from the `LocalLocator` without cloning it.
+    ///
+    /// Only values inserted using `insert` can be borrowed,
+    /// this returns `None` for values created by a factory.
+    pub fn get_ref<T>(&self) -> Option<&T>
+    where
+        T: 'static,
+    {
+        match self.providers.get(&TypeId::of::<T>())? {
+            (LocalProvider::Single { value, .. }, _) => value.downcast_ref::<T>(),
+            (LocalProvider::Factory(_), _) => None,
+        }
+    }
+
+    /// Returns `true` if the `LocalLocator` contains a value of type `T`.
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.providers.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Removes the value of type `T` from the `LocalLocator`, returns `true` if it was removed.
+    pub fn remove<T: 'static>(&mut self) -> bool {
+        self.providers.remove(&TypeId::of::<T>()).is_some()
+    }
+
+    /// Returns the number of services in the `LocalLocator`.
+    pub fn len(&self) -> usize {
+        self.providers.len()
+    }
+
+    /// Returns `true` if the `LocalLocator` contains no services.
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+
+    /// Invoke the given function injecting the dependencies from this locator.
+    pub fn invoke<F, Args>(&self, f: F) -> Result<F::Output, LocatorError>
+    where
+        F: Invoke<Args>,
+        Args: FromLocalLocator,
+    {
+        let args = Args::from_local_locator(self)?;
+        Ok(Invoke::call(f, args))
+    }
+}
+
+impl Debug for LocalLocator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut names = self
+            .providers
+            .values()
+            .map(|(_, name)| *name)
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+
+        f.debug_struct("LocalLocator")
+            .field("services", &names)
+            .finish()
+    }
+}
+
+/// A type that can be constructed from a `LocalLocator`.
+pub trait FromLocalLocator: Sized {
+    /// Constructs this type from the given `LocalLocator`.
+    fn from_local_locator(locator: &LocalLocator) -> Result<Self, LocatorError>;
+}
+
+macro_rules! impl_from_local_locator_for_tuple {
+    ( $($ty:ident),* ) => {
+        impl<$($ty: 'static),*> FromLocalLocator for ($($ty,)*) {
+            fn from_local_locator(locator: &LocalLocator) -> Result<Self, LocatorError> {
+                Ok((
+                    $(
+                        locator.get::<$ty>().ok_or_else(LocatorError::not_found::<$ty>)?
+                    ,)*
+                ))
+            }
+        }
+    };
+}
+
+impl_from_local_locator_for_tuple!(A);
+impl_from_local_locator_for_tuple!(A, B);
+impl_from_local_locator_for_tuple!(A, B, C);
+impl_from_local_locator_for_tuple!(A, B, C, D);
+impl_from_local_locator_for_tuple!(A, B, C, D, E);
+impl_from_local_locator_for_tuple!(A, B, C, D, E, F);
+impl_from_local_locator_for_tuple!(A, B, C, D, E, F, G);
+impl_from_local_locator_for_tuple!(A, B, C, D, E, F, G, H);
+impl_from_local_locator_for_tuple!(A, B, C, D, E, F, G, H, I);
+impl_from_local_locator_for_tuple!(A, B, C, D, E, F, G, H, I, J);
+impl_from_local_locator_for_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+impl_from_local_locator_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
+impl_from_local_locator_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M);
+impl_from_local_locator_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N);
+impl_from_local_locator_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O);
+impl_from_local_locator_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::Cell, rc::Rc};
+
+    #[test]
+    fn test_insert_and_get_rc() {
+        let mut locator = LocalLocator::new();
+        let counter = Rc::new(Cell::new(0));
+        locator.insert(counter.clone());
+
+        let resolved = locator.get::<Rc<Cell<i32>>>().unwrap();
+        resolved.set(42);
+
+        assert_eq!(counter.get(), 42);
+        assert_eq!(locator.get_ref::<Rc<Cell<i32>>>().unwrap().get(), 42);
+    }
+
+    #[test]
+    fn test_insert_with_and_invoke() {
+        let mut locator = LocalLocator::new();
+        locator.insert(Rc::new(Cell::new(10)));
+        locator.insert_with(|locator| {
+            let value = locator.get::<Rc<Cell<i32>>>().unwrap();
+            format!("value: {}", value.get())
+        });
+
+        let result = locator.invoke(|counter: Rc<Cell<i32>>, text: String| {
+            counter.set(counter.get() + 1);
+            text
+        });
+
+        assert_eq!(result.unwrap(), "value: 10");
+        assert_eq!(locator.get::<Rc<Cell<i32>>>().unwrap().get(), 11);
+        assert!(locator.get_ref::<String>().is_none());
+    }
+
+    #[test]
+    fn test_invoke_not_found() {
+        let locator = LocalLocator::new();
+        let result = locator.invoke(|_: Rc<Cell<i32>>| ());
+
+        assert!(matches!(result, Err(LocatorError::NotFound { .. })));
+    }
+}
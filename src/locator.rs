@@ -1,204 +1,4124 @@
 #![allow(clippy::type_complexity)]
 
 use std::{
-    any::{Any, TypeId},
-    collections::HashMap,
+    any::{type_name, Any, TypeId},
+    collections::{
+        hash_map::{self, RandomState},
+        HashMap,
+    },
+    fmt::{Debug, Formatter},
     future::Future,
+    hash::BuildHasher,
+    marker::PhantomData,
+    pin::{pin, Pin},
+    sync::{Arc, Mutex, OnceLock, PoisonError, RwLock, RwLockWriteGuard, Weak},
+    task::Poll,
+    time::{Duration, Instant},
 };
-use crate::{AsyncInvoke, FromLocator, Invoke, LocatorError};
+use crate::{
+    cycle, graph, timer,
+    trace::{self, Resolving},
+    AsyncInvoke, FromLocator, FromLocatorRef, Invoke, Lazy, LocatorBuilder,
+    LocatorError, LocatorInfo, MetricsCollector, Module, Mut, OccupiedEntry, ProviderKind,
+    RetryPolicy, ServiceEntry, ServiceInfo, VacantEntry,
+};
+
+/// A wrapper that stores the services from a locator.
+pub enum Provider<S = RandomState> {
+    Single(Singleton),
+    Cached(Cached<S>),
+    Factory(Arc<dyn Fn(&Locator<S>) -> Box<dyn Any + Send + Sync> + Send + Sync>),
+    AsyncFactory(
+        Arc<
+            dyn Fn(&Locator<S>) -> Pin<Box<dyn Future<Output = Box<dyn Any + Send + Sync>> + Send>>
+                + Send
+                + Sync,
+        >,
+    ),
+}
+
+impl<S> Provider<S> {
+    /// Returns how this provider creates its values.
+    pub fn kind(&self) -> ProviderKind {
+        match self {
+            Provider::Single(_) => ProviderKind::Single,
+            Provider::Cached(_) => ProviderKind::Cached,
+            Provider::Factory(_) => ProviderKind::Factory,
+            Provider::AsyncFactory(_) => ProviderKind::AsyncFactory,
+        }
+    }
+}
+
+impl<S> Clone for Provider<S> {
+    fn clone(&self) -> Self {
+        match self {
+            Provider::Single(singleton) => Provider::Single(singleton.clone()),
+            Provider::Cached(cached) => Provider::Cached(cached.clone()),
+            Provider::Factory(f) => Provider::Factory(f.clone()),
+            Provider::AsyncFactory(f) => Provider::AsyncFactory(f.clone()),
+        }
+    }
+}
+
+/// A value created once and cloned from the cached instance on each resolution.
+///
+/// Clones of a `Singleton` share the same cached instance. The instance is stored in a `OnceLock`,
+/// so if it is resolved concurrently for the first time the other threads wait for it to be created once.
+#[derive(Clone)]
+pub struct Singleton {
+    instance: Arc<Instance>,
+    clone: fn(&(dyn Any + Send + Sync)) -> Box<dyn Any + Send + Sync>,
+}
+
+/// The instance of a `Singleton`, created by its factory or given when the `Singleton` is created.
+enum Instance {
+    Lazy {
+        init: Box<dyn Fn() -> Box<dyn Any + Send + Sync> + Send + Sync>,
+        value: OnceLock<Box<dyn Any + Send + Sync>>,
+    },
+    Ready(Box<dyn Any + Send + Sync>),
+}
+
+impl Instance {
+    fn get(&self) -> &(dyn Any + Send + Sync) {
+        match self {
+            Instance::Lazy { init, value } => value.get_or_init(init).as_ref(),
+            Instance::Ready(value) => value.as_ref(),
+        }
+    }
+
+    fn into_value(self) -> Box<dyn Any + Send + Sync> {
+        match self {
+            Instance::Lazy { init, value } => value.into_inner().unwrap_or_else(init),
+            Instance::Ready(value) => value,
+        }
+    }
+}
+
+impl Singleton {
+    /// Creates a `Singleton` that runs `init` the first time the value is requested.
+    pub fn new<F, T>(init: F) -> Self
+    where
+        F: Fn() -> T + Send + Sync + 'static,
+        T: Send + Sync + Clone + 'static,
+    {
+        Singleton {
+            instance: Arc::new(Instance::Lazy {
+                init: Box::new(move || Box::new(init())),
+                value: OnceLock::new(),
+            }),
+            clone: clone_any::<T>,
+        }
+    }
+
+    /// Creates a `Singleton` that already holds the given value, so the value is only cloned when requested.
+    pub fn from_value<T>(value: T) -> Self
+    where
+        T: Send + Sync + Clone + 'static,
+    {
+        Singleton {
+            instance: Arc::new(Instance::Ready(Box::new(value))),
+            clone: clone_any::<T>,
+        }
+    }
+
+    /// Returns a clone of the cached value, initializing it if needed.
+    pub fn get(&self) -> Box<dyn Any + Send + Sync> {
+        (self.clone)(self.get_ref())
+    }
+
+    /// Returns a reference to the cached value, initializing it if needed.
+    pub fn get_ref(&self) -> &(dyn Any + Send + Sync) {
+        self.instance.get()
+    }
+
+    /// Returns the cached value initializing it if needed, the value is only cloned
+    /// if it is shared with other clones of this `Singleton`.
+    pub fn into_value(self) -> Box<dyn Any + Send + Sync> {
+        match Arc::try_unwrap(self.instance) {
+            Ok(instance) => instance.into_value(),
+            Err(instance) => (self.clone)(instance.get()),
+        }
+    }
+}
+
+/// A value created by a factory the first time it is resolved and cloned from the cached instance
+/// on each following resolution, see `Locator::insert_cached_with`.
+///
+/// Clones of a `Cached` share the same cached instance.
+pub struct Cached<S = RandomState> {
+    init: Arc<dyn Fn(&Locator<S>) -> Box<dyn Any + Send + Sync> + Send + Sync>,
+    clone: fn(&(dyn Any + Send + Sync)) -> Box<dyn Any + Send + Sync>,
+    value: Arc<OnceLock<Box<dyn Any + Send + Sync>>>,
+}
+
+impl<S> Cached<S> {
+    /// Creates a `Cached` that runs `init` the first time the value is requested.
+    pub fn new<F, T>(init: F) -> Self
+    where
+        F: Fn(&Locator<S>) -> T + Send + Sync + 'static,
+        T: Send + Sync + Clone + 'static,
+    {
+        Cached {
+            init: Arc::new(move |locator| Box::new(init(locator))),
+            clone: clone_any::<T>,
+            value: Arc::new(OnceLock::new()),
+        }
+    }
+
+    /// Returns a clone of the cached value, initializing it using the given locator if needed.
+    pub fn get(&self, locator: &Locator<S>) -> Box<dyn Any + Send + Sync> {
+        (self.clone)(self.get_ref(locator))
+    }
+
+    /// Returns a reference to the cached value, initializing it using the given locator if needed.
+    pub fn get_ref(&self, locator: &Locator<S>) -> &(dyn Any + Send + Sync) {
+        self.value.get_or_init(|| (self.init)(locator)).as_ref()
+    }
+
+    /// Returns the address of the shared instance, which identifies this `Cached` and its clones.
+    fn addr(&self) -> usize {
+        Arc::as_ptr(&self.value) as usize
+    }
+}
+
+impl<S> Clone for Cached<S> {
+    fn clone(&self) -> Self {
+        Cached {
+            init: self.init.clone(),
+            clone: self.clone,
+            value: self.value.clone(),
+        }
+    }
+}
+
+fn clone_any<T: Clone + Send + Sync + 'static>(
+    value: &(dyn Any + Send + Sync),
+) -> Box<dyn Any + Send + Sync> {
+    let value = value
+        .downcast_ref::<T>()
+        .expect("cached value type mismatch");
+    Box::new(value.clone())
+}
+
+/// How to handle the services registered in both locators when merging them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergeStrategy {
+    /// The services of the other locator replace the existing ones.
+    #[default]
+    Overwrite,
+
+    /// The existing services are kept and the ones of the other locator are ignored.
+    KeepExisting,
+
+    /// The merge fails if any service is registered in both locators.
+    Error,
+}
+
+/// How long a value created by a factory is reused, see `Locator::register`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lifetime {
+    /// The factory runs on each resolution.
+    Transient,
+
+    /// The factory runs once and the value is shared by the locator and all its scopes.
+    Singleton,
+
+    /// The factory runs once for each locator that resolves the value,
+    /// so each scope created with `Locator::scope` has its own value.
+    Scoped,
+}
+
+/// The key of a service, named services don't collide with the unnamed ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Key {
+    type_id: TypeId,
+    name: Option<&'static str>,
+}
+
+impl Key {
+    fn of<T: 'static>() -> Self {
+        Key::unnamed(TypeId::of::<T>())
+    }
+
+    fn named<T: 'static>(name: &'static str) -> Self {
+        Key {
+            type_id: TypeId::of::<T>(),
+            name: Some(name),
+        }
+    }
+
+    fn unnamed(type_id: TypeId) -> Self {
+        Key {
+            type_id,
+            name: None,
+        }
+    }
+}
+
+/// A provider and the name of the type it provides.
+pub(crate) struct Entry<S> {
+    pub(crate) provider: Provider<S>,
+    type_name: &'static str,
+    verify: Option<Verify<S>>,
+    warm_up: Option<WarmUp<S>>,
+    retry: Option<RetryPolicy>,
+    fallible: bool,
+    scoped: bool,
+}
+
+impl<S> Clone for Entry<S> {
+    fn clone(&self) -> Self {
+        Entry {
+            provider: self.provider.clone(),
+            type_name: self.type_name,
+            verify: self.verify,
+            warm_up: self.warm_up,
+            retry: self.retry.clone(),
+            fallible: self.fallible,
+            scoped: self.scoped,
+        }
+    }
+}
+
+impl<S: BuildHasher> Entry<S> {
+    /// Creates an entry for a provider of `T` that is checked by `verify`.
+    pub(crate) fn typed<T>(provider: Provider<S>) -> Self
+    where
+        T: Send + Sync + 'static,
+    {
+        Entry {
+            provider,
+            type_name: type_name::<T>(),
+            verify: Some(verify_provider::<T, S>),
+            warm_up: None,
+            retry: None,
+            fallible: false,
+            scoped: false,
+        }
+    }
+}
+
+/// Checks that a provider resolves to the type it was registered for.
+pub(crate) type Verify<S> = fn(&Locator<S>, &Provider<S>) -> Result<(), LocatorError>;
+
+/// Returned by factories that cannot produce a value, fails to downcast to any service type.
+///
+/// `resolve` fails with the error if any, otherwise with `LocatorError::NotFound`.
+struct Unresolved(Option<LocatorError>);
+
+impl Unresolved {
+    /// Returns the error of the value that failed to resolve, if it is an `Unresolved` with an error.
+    fn error(value: Option<Box<dyn Any + Send + Sync>>) -> Option<LocatorError> {
+        value?.downcast::<Unresolved>().ok()?.0
+    }
+}
+
+/// Resolves an async singleton so its value is cached, see `Locator::initialize_all_async`.
+type WarmUp<S> = for<'a> fn(
+    &'a Locator<S>,
+    &'a Provider<S>,
+) -> Pin<Box<dyn Future<Output = Result<(), LocatorError>> + Send + 'a>>;
+
+fn warm_up_provider<'a, T, S>(
+    locator: &'a Locator<S>,
+    provider: &'a Provider<S>,
+) -> Pin<Box<dyn Future<Output = Result<(), LocatorError>> + Send + 'a>>
+where
+    T: Send + Sync + 'static,
+    S: BuildHasher + Send + Sync,
+{
+    Box::pin(async move {
+        let value = match provider {
+            Provider::AsyncFactory(f) => f(locator).await,
+            _ => return Ok(()),
+        };
+
+        value
+            .downcast::<T>()
+            .map(drop)
+            .map_err(|_| LocatorError::not_found::<T>())
+    })
+}
+
+/// Polls all the futures concurrently and returns the errors of the ones that failed.
+async fn join_all<'a>(
+    futures: Vec<Pin<Box<dyn Future<Output = Result<(), LocatorError>> + Send + 'a>>>,
+) -> Vec<LocatorError> {
+    let mut pending = futures.into_iter().map(Some).collect::<Vec<_>>();
+    let mut errors = Vec::new();
+
+    std::future::poll_fn(|cx| {
+        for slot in pending.iter_mut() {
+            if let Some(fut) = slot {
+                if let Poll::Ready(result) = fut.as_mut().poll(cx) {
+                    errors.extend(result.err());
+                    *slot = None;
+                }
+            }
+        }
+
+        if pending.iter().all(Option::is_none) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    })
+    .await;
+
+    errors
+}
+
+fn verify_provider<T, S>(locator: &Locator<S>, provider: &Provider<S>) -> Result<(), LocatorError>
+where
+    T: Send + Sync + 'static,
+    S: BuildHasher,
+{
+    match provider {
+        Provider::AsyncFactory(_) => Ok(()),
+        _ => match locator.resolve_value::<T>(provider) {
+            Ok(_) => Ok(()),
+            Err(value) => {
+                Err(Unresolved::error(value).unwrap_or_else(LocatorError::not_found::<T>))
+            }
+        },
+    }
+}
+
+/// A service locator.
+///
+/// Cloning a locator is cheap, the clone shares the providers but can be modified independently.
+///
+/// The locator is generic over the hasher used to lookup the services.
+pub struct Locator<S = RandomState> {
+    providers: HashMap<Key, Entry<S>, S>,
+    multi: HashMap<TypeId, Vec<Provider<S>>, S>,
+    parent: Option<Arc<Locator<S>>>,
+    metrics: Option<Arc<dyn MetricsCollector>>,
+    scoped: Arc<RwLock<ScopedValues>>,
+    shutdown_hooks: Vec<ShutdownHook<S>>,
+}
+
+type ShutdownHook<S> = Box<dyn FnOnce(&Locator<S>) + Send + Sync>;
+
+/// The values with `Lifetime::Scoped` created for a locator, shared so a `Checkpoint` can keep them.
+type ScopedValues = HashMap<TypeId, Arc<dyn Any + Send + Sync>>;
+
+/// The services of a `Locator` at some point, see `Locator::checkpoint`.
+pub struct Checkpoint<S = RandomState> {
+    providers: HashMap<Key, Entry<S>, S>,
+    multi: HashMap<TypeId, Vec<Provider<S>>, S>,
+    scoped: ScopedValues,
+}
+
+/// A `Locator` that uses `FxHasher` to lookup the services, which is faster for `TypeId` keys
+/// than the default hasher.
+#[cfg(feature = "fxhash")]
+pub type FastLocator = Locator<std::hash::BuildHasherDefault<fxhash::FxHasher>>;
+
+impl<S: Clone> Clone for Locator<S> {
+    /// The values with `Lifetime::Scoped` and the shutdown hooks are not cloned,
+    /// the clone creates its own values.
+    fn clone(&self) -> Self {
+        Locator {
+            providers: self.providers.clone(),
+            multi: self.multi.clone(),
+            parent: self.parent.clone(),
+            metrics: self.metrics.clone(),
+            scoped: Arc::default(),
+            shutdown_hooks: Vec::new(),
+        }
+    }
+}
+
+impl<S: Default> Default for Locator<S> {
+    fn default() -> Self {
+        Locator {
+            providers: HashMap::default(),
+            multi: HashMap::default(),
+            parent: None,
+            metrics: None,
+            scoped: Arc::default(),
+            shutdown_hooks: Vec::new(),
+        }
+    }
+}
+
+impl<S: BuildHasher> Locator<S> {
+    /// Inserts a provider without checking the types, the type name is recorded as `<unknown>`.
+    #[inline]
+    pub fn unchecked_insert(&mut self, id: TypeId, provider: Provider<S>) -> Option<Provider<S>> {
+        self.unchecked_insert_with_type_name(id, "<unknown>", provider)
+    }
+
+    /// Inserts a provider without checking the types, recording the name of the type it provides.
+    #[inline]
+    pub fn unchecked_insert_with_type_name(
+        &mut self,
+        id: TypeId,
+        type_name: &'static str,
+        provider: Provider<S>,
+    ) -> Option<Provider<S>> {
+        self.insert_entry(Key::unnamed(id), type_name, provider, None)
+    }
+
+    /// Inserts a provider that is checked by `verify` using the given function.
+    pub(crate) fn insert_verified(
+        &mut self,
+        id: TypeId,
+        type_name: &'static str,
+        provider: Provider<S>,
+        verify: Verify<S>,
+    ) -> Option<Provider<S>> {
+        let key = Key::unnamed(id);
+        let prev = self.insert_entry(key, type_name, provider, Some(verify));
+        if let Some(entry) = self.providers.get_mut(&key) {
+            entry.fallible = true;
+        }
+
+        prev
+    }
+
+    fn insert_typed<T>(&mut self, key: Key, provider: Provider<S>) -> Option<Provider<S>>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.providers
+            .insert(key, Entry::typed::<T>(provider))
+            .map(|e| e.provider)
+    }
+
+    fn insert_entry(
+        &mut self,
+        key: Key,
+        type_name: &'static str,
+        provider: Provider<S>,
+        verify: Option<Verify<S>>,
+    ) -> Option<Provider<S>> {
+        let entry = Entry {
+            provider,
+            type_name,
+            verify,
+            warm_up: None,
+            retry: None,
+            fallible: false,
+            scoped: false,
+        };
+
+        self.providers.insert(key, entry).map(|e| e.provider)
+    }
+
+    /// Sets the policy used to retry the fallible async factory of the given type.
+    pub(crate) fn set_retry_policy(&mut self, id: TypeId, policy: RetryPolicy) {
+        if let Some(entry) = self.providers.get_mut(&Key::unnamed(id)) {
+            entry.retry = Some(policy);
+        }
+    }
+
+    /// Returns the policy used to retry the fallible async factory of the given type.
+    pub(crate) fn retry_policy(&self, id: &TypeId) -> Option<RetryPolicy> {
+        match self.providers.get(&Key::unnamed(*id)) {
+            Some(entry) => entry.retry.clone(),
+            None => self.parent.as_ref()?.retry_policy(id),
+        }
+    }
+
+    /// Gets a provider for the given type without checking if the types matches.
+    ///
+    /// If this locator is a scope, the parent locators are also checked.
+    #[inline]
+    pub fn unchecked_get(&self, id: &TypeId) -> Option<&Provider<S>> {
+        self.get_provider(&Key::unnamed(*id))
+    }
+
+    fn get_provider(&self, key: &Key) -> Option<&Provider<S>> {
+        self.get_entry(key).map(|entry| &entry.provider)
+    }
+
+    fn get_entry(&self, key: &Key) -> Option<&Entry<S>> {
+        match self.providers.get(key) {
+            Some(entry) => Some(entry),
+            None => self.parent.as_ref()?.get_entry(key),
+        }
+    }
+
+    /// Resolves the provider, in debug builds panics if the provider returns a value of other type,
+    /// use `resolve` to get the error instead.
+    pub(crate) fn resolve_provider<T>(&self, provider: &Provider<S>) -> Option<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        match self.resolve_value(provider) {
+            Ok(value) => Some(value),
+            Err(value) => {
+                debug_assert!(
+                    value.is_none_or(|value| value.is::<Unresolved>()),
+                    "the provider of `{}` returned a value of other type, \
+                    check the providers inserted with `unchecked_insert`",
+                    type_name::<T>()
+                );
+
+                None
+            }
+        }
+    }
+
+    /// Resolves the provider, on failure returns the value that couldn't be downcasted to `T`
+    /// or `None` if the provider cannot be resolved synchronously.
+    fn resolve_value<T>(
+        &self,
+        provider: &Provider<S>,
+    ) -> Result<T, Option<Box<dyn Any + Send + Sync>>>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.resolve_with(type_name::<T>(), provider, true, |value| {
+            value.downcast::<T>().map(|x| *x)
+        })
+    }
+
+    /// Resolves the provider of a service inserted by `try_insert_with`, which tracks the cyclic dependencies itself.
+    pub(crate) fn resolve_fallible<T>(
+        &self,
+        provider: &Provider<S>,
+    ) -> Option<Result<T, LocatorError>>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.resolve_with(
+            type_name::<Result<T, LocatorError>>(),
+            provider,
+            false,
+            |value| value.downcast::<Result<T, LocatorError>>().map(|x| *x),
+        )
+        .ok()
+    }
+
+    /// Resolves the provider of the service with the given type name, recording the resolution
+    /// in the tracing spans, the dependency graph and the metrics.
+    ///
+    /// `convert` returns the resolved value or the value it couldn't convert. If `guarded` the factories run
+    /// inside `cycle::guard_provider`, a cyclic dependency returns `Unresolved` with the `LocatorError::Cyclic` error.
+    fn resolve_with<R>(
+        &self,
+        name: &'static str,
+        provider: &Provider<S>,
+        guarded: bool,
+        convert: impl FnOnce(Box<dyn Any + Send + Sync>) -> Result<R, Box<dyn Any + Send + Sync>>,
+    ) -> Result<R, Option<Box<dyn Any + Send + Sync>>> {
+        let _tracking = graph::enter(name);
+        let resolving = Resolving::start(name);
+        let start = self.metrics.is_some().then(Instant::now);
+        let guard = |addr: usize, f: &dyn Fn() -> Box<dyn Any + Send + Sync>| {
+            if !guarded {
+                return f();
+            }
+
+            cycle::guard_provider(addr, name, f)
+                .unwrap_or_else(|err| Box::new(Unresolved(Some(err))))
+        };
+
+        let value = match provider {
+            Provider::Single(singleton) => Some(singleton.get()),
+            Provider::Cached(cached) => Some(guard(cached.addr(), &|| cached.get(self))),
+            Provider::Factory(f) => Some(guard(Arc::as_ptr(f) as *const () as usize, &|| f(self))),
+            Provider::AsyncFactory(_) => None,
+        };
+
+        let value = match value {
+            Some(value) => convert(value).map_err(Some),
+            None => Err(None),
+        };
+
+        resolving.finish(value.is_ok());
+        if value.is_ok() {
+            self.record(name, start);
+        }
+
+        value
+    }
+
+    fn record(&self, name: &'static str, start: Option<Instant>) {
+        if let (Some(metrics), Some(start)) = (&self.metrics, start) {
+            metrics.record(name, start.elapsed());
+        }
+    }
+}
+
+impl Locator {
+    /// Creates a new `Locator`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Creates a new `Locator` with space for at least `capacity` services.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Locator {
+            providers: HashMap::with_capacity(capacity),
+            ..Default::default()
+        }
+    }
+
+    /// Returns a builder to create a `Locator` that checks its services before returning it.
+    pub fn builder() -> LocatorBuilder {
+        LocatorBuilder::new()
+    }
+}
+
+impl<S: BuildHasher> Locator<S> {
+    /// Creates a new `Locator` which will use the given hasher to lookup the services.
+    pub fn with_hasher(hasher: S) -> Self
+    where
+        S: Clone,
+    {
+        Locator {
+            providers: HashMap::with_hasher(hasher.clone()),
+            multi: HashMap::with_hasher(hasher),
+            parent: None,
+            metrics: None,
+            scoped: Arc::default(),
+            shutdown_hooks: Vec::new(),
+        }
+    }
+
+    /// Creates a child `Locator` that falls back to this locator for the services it doesn't have.
+    ///
+    /// Services inserted in the child shadow the ones in the parent and never modify it,
+    /// factories found in a parent are called with the child locator that requested them.
+    pub fn scope(self: &Arc<Self>) -> Locator<S>
+    where
+        S: Default,
+    {
+        Locator {
+            parent: Some(self.clone()),
+            metrics: self.metrics.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Calls the given function with a scope of this locator where `value` overrides the service of type `T`,
+    /// the scope is discarded after the call so this locator is never modified.
+    ///
+    /// Factories resolved in the scope also see the override.
+    pub fn with_override<T, F, R>(self: &Arc<Self>, value: T, f: F) -> R
+    where
+        T: Send + Sync + Clone + 'static,
+        F: FnOnce(&Locator<S>) -> R,
+        S: Default,
+    {
+        let mut scope = self.scope();
+        scope.insert(value);
+        f(&scope)
+    }
+
+    /// Sets the collector that records each service resolved by this locator and its scopes.
+    pub fn with_metrics<M>(mut self, collector: M) -> Self
+    where
+        M: MetricsCollector + 'static,
+    {
+        self.metrics = Some(Arc::new(collector));
+        self
+    }
+
+    /// Inserts a value of type `T` and returns the locator, see `Locator::insert`.
+    ///
+    /// This allows building a locator inline, for example `Arc::new(Locator::new().with(config))`.
+    pub fn with<T>(mut self, value: T) -> Self
+    where
+        T: Send + Sync + Clone + 'static,
+    {
+        self.insert(value);
+        self
+    }
+
+    /// Inserts a factory for a value of type `T` and returns the locator, see `Locator::insert_with`.
+    pub fn with_factory<F, T>(mut self, factory: F) -> Self
+    where
+        F: Fn(&Self) -> T + 'static + Send + Sync,
+        T: Send + Sync + 'static,
+    {
+        self.insert_with(factory);
+        self
+    }
+
+    /// Inserts a value of type `T` into the `Locator`.
+    pub fn insert<T>(&mut self, value: T) -> Option<Provider<S>>
+    where
+        T: Send + Sync + Clone + 'static,
+    {
+        let provider = Provider::Single(Singleton::from_value(value));
+        self.insert_typed::<T>(Key::of::<T>(), provider)
+    }
+
+    /// Inserts a value of type `T` into the `Locator` using a factory function that takes a `Locator` as input.
+    ///
+    /// The factory runs on each resolution, use `insert_cached_with` to create the value once.
+    pub fn insert_with<F, T>(&mut self, factory: F) -> Option<Provider<S>>
+    where
+        F: Fn(&Self) -> T + 'static + Send + Sync,
+        T: Send + Sync + 'static,
+    {
+        let provider = Provider::Factory(Arc::new(move |locator| {
+            let value = factory(locator);
+            Box::new(value)
+        }));
+
+        self.insert_typed::<T>(Key::of::<T>(), provider)
+    }
+
+    /// Inserts a value of type `T` into the `Locator` using a factory function that runs
+    /// the first time the value is resolved, the next resolutions return a clone of that value.
+    pub fn insert_cached_with<F, T>(&mut self, factory: F) -> Option<Provider<S>>
+    where
+        F: Fn(&Self) -> T + 'static + Send + Sync,
+        T: Send + Sync + Clone + 'static,
+    {
+        let provider = Provider::Cached(Cached::new(factory));
+        self.insert_typed::<T>(Key::of::<T>(), provider)
+    }
+
+    /// Inserts a value of type `T` created by the given factory, the lifetime determines
+    /// how long each value created by the factory is reused.
+    ///
+    /// `Lifetime::Transient` is the same as `insert_with` and `Lifetime::Singleton` as `insert_cached_with`.
+    pub fn register<T, F>(&mut self, lifetime: Lifetime, factory: F) -> Option<Provider<S>>
+    where
+        F: Fn(&Self) -> T + 'static + Send + Sync,
+        T: Send + Sync + Clone + 'static,
+    {
+        match lifetime {
+            Lifetime::Transient => self.insert_with(factory),
+            Lifetime::Singleton => self.insert_cached_with(factory),
+            Lifetime::Scoped => {
+                self.scoped_values().remove(&TypeId::of::<T>());
+                let prev = self.insert_with(move |locator| locator.get_scoped(&factory));
+                if let Some(entry) = self.providers.get_mut(&Key::of::<T>()) {
+                    entry.scoped = true;
+                }
+
+                prev
+            }
+        }
+    }
+
+    /// Inserts a value of type `T` created by the given factory at most once for each locator that resolves it,
+    /// this is the same as `register(Lifetime::Scoped, factory)`.
+    ///
+    /// The value is cached in the scope that requested it and never in the parent,
+    /// so each scope created with `Locator::scope` has its own instance.
+    pub fn register_scoped<T, F>(&mut self, factory: F) -> Option<Provider<S>>
+    where
+        F: Fn(&Self) -> T + 'static + Send + Sync,
+        T: Send + Sync + Clone + 'static,
+    {
+        self.register(Lifetime::Scoped, factory)
+    }
+
+    /// Inserts a value of type `T` created by the given factory the first time is resolved,
+    /// the next resolutions return a clone of that value. This is the same as `insert_cached_with`.
+    ///
+    /// Unlike `insert_singleton` the value is created by a factory that can resolve other services.
+    /// Those dependencies are snapshotted at the first resolution, replacing them later
+    /// doesn't change the cached value.
+    pub fn register_factory_once<T, F>(&mut self, factory: F) -> Option<Provider<S>>
+    where
+        F: Fn(&Self) -> T + 'static + Send + Sync,
+        T: Send + Sync + Clone + 'static,
+    {
+        self.insert_cached_with(factory)
+    }
+
+    fn scoped_values(&mut self) -> RwLockWriteGuard<'_, ScopedValues> {
+        self.scoped.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the value of `T` created for this locator or creates it using the factory.
+    ///
+    /// The lock is not held while the factory runs, so it can resolve other scoped values.
+    fn get_scoped<T, F>(&self, factory: &F) -> T
+    where
+        F: Fn(&Self) -> T,
+        T: Send + Sync + Clone + 'static,
+    {
+        let id = TypeId::of::<T>();
+        let cached = self.scoped.read().unwrap_or_else(PoisonError::into_inner);
+        if let Some(value) = cached.get(&id).and_then(|x| x.downcast_ref::<T>()) {
+            return value.clone();
+        }
+
+        drop(cached);
+        let value = factory(self);
+        let mut cached = self.scoped.write().unwrap_or_else(PoisonError::into_inner);
+
+        // If the factory ran concurrently, the first value stored is kept.
+        cached
+            .entry(id)
+            .or_insert_with(|| Arc::new(value))
+            .downcast_ref::<T>()
+            .expect("scoped value type mismatch")
+            .clone()
+    }
+
+    /// Inserts a value of type `T` only if the type is not already in the `Locator`,
+    /// returns `true` if the value was inserted.
+    pub fn insert_if_absent<T>(&mut self, value: T) -> bool
+    where
+        T: Send + Sync + Clone + 'static,
+    {
+        if self.contains::<T>() {
+            return false;
+        }
+
+        self.insert(value);
+        true
+    }
+
+    /// Inserts a factory for a value of type `T` only if the type is not already in the `Locator`,
+    /// returns `true` if the factory was inserted.
+    pub fn insert_with_if_absent<F, T>(&mut self, factory: F) -> bool
+    where
+        F: Fn(&Self) -> T + 'static + Send + Sync,
+        T: Send + Sync + 'static,
+    {
+        if self.contains::<T>() {
+            return false;
+        }
+
+        self.insert_with(factory);
+        true
+    }
+
+    /// Inserts a factory for a value of type `T` that uses `fallback` when `primary` returns `None`,
+    /// if both return `None` the value cannot be resolved and `get` returns `None`.
+    ///
+    /// For services inserted with `TryLocator::try_insert_with` the factory can fall back on errors
+    /// directly, for example `try_insert_with(|l| primary(l).or_else(|_| fallback(l)))`.
+    pub fn insert_with_fallback<P, F, T>(&mut self, primary: P, fallback: F) -> Option<Provider<S>>
+    where
+        P: Fn(&Self) -> Option<T> + 'static + Send + Sync,
+        F: Fn(&Self) -> Option<T> + 'static + Send + Sync,
+        T: Send + Sync + 'static,
+    {
+        let provider = Provider::Factory(Arc::new(move |locator| {
+            match primary(locator).or_else(|| fallback(locator)) {
+                Some(value) => Box::new(value),
+                None => Box::new(Unresolved(None)),
+            }
+        }));
+
+        self.insert_typed::<T>(Key::of::<T>(), provider)
+    }
+
+    /// Inserts a factory for a value of type `T` that can fail, `resolve` returns the error of the factory
+    /// and `get` returns `None`.
+    ///
+    /// Used by `bind_trait!`, services that can fail should be inserted with `TryLocator::try_insert_with`.
+    #[doc(hidden)]
+    pub fn insert_fallible_with<F, T>(&mut self, factory: F) -> Option<Provider<S>>
+    where
+        F: Fn(&Self) -> Result<T, LocatorError> + 'static + Send + Sync,
+        T: Send + Sync + 'static,
+    {
+        let provider = Provider::Factory(Arc::new(move |locator| match factory(locator) {
+            Ok(value) => Box::new(value),
+            Err(err) => Box::new(Unresolved(Some(err))),
+        }));
+
+        self.insert_typed::<T>(Key::of::<T>(), provider)
+    }
+
+    /// Inserts a service of type `U` created by resolving the service of type `T` and converting it
+    /// with the given function, for example to expose a concrete type as a trait object.
+    ///
+    /// The service of type `U` fails to resolve if there is no service of type `T`.
+    pub fn alias<T, U, F>(&mut self, f: F) -> Option<Provider<S>>
+    where
+        F: Fn(T) -> U + 'static + Send + Sync,
+        T: Send + Sync + 'static,
+        U: Send + Sync + 'static,
+    {
+        let provider = Provider::Factory(Arc::new(move |locator| match locator.get::<T>() {
+            Some(value) => Box::new(f(value)),
+            None => Box::new(Unresolved(None)),
+        }));
+
+        self.insert_typed::<U>(Key::of::<U>(), provider)
+    }
+
+    /// Replaces the service of type `T` with a service of type `U` created by converting the current value
+    /// with the given function, for example to migrate a concrete type to a wrapper.
+    ///
+    /// Unlike `alias` the service of type `T` is removed and the conversion runs once, a value inserted
+    /// with `insert` is moved out the same as `take`. Returns `false` if the service of type `T` is not
+    /// in this locator, the parent locators are not searched, or if it cannot be resolved, in which case it is kept.
+    pub fn map_service<T, U, F>(&mut self, f: F) -> bool
+    where
+        F: FnOnce(T) -> U,
+        T: Send + Sync + 'static,
+        U: Send + Sync + Clone + 'static,
+    {
+        let Some(value) = self.take_resolved::<T>() else {
+            return false;
+        };
+
+        self.insert(f(value));
+        true
+    }
+
+    /// Wraps the registered service of type `T` with the given decorator, `get` returns the decorated value.
+    ///
+    /// The decorators are applied in the order they were registered,
+    /// returns `false` if there is no service of type `T` to decorate.
+    pub fn decorate<T, F>(&mut self, f: F) -> bool
+    where
+        F: Fn(T, &Self) -> T + 'static + Send + Sync,
+        T: Send + Sync + 'static,
+        S: 'static,
+    {
+        let key = Key::of::<T>();
+        let Some(prev) = self.get_provider(&key).cloned() else {
+            return false;
+        };
+
+        let provider = Provider::Factory(Arc::new(move |locator| {
+            let value = match &prev {
+                Provider::Single(singleton) => singleton.get(),
+                Provider::Cached(cached) => cached.get(locator),
+                Provider::Factory(factory) => factory(locator),
+                Provider::AsyncFactory(_) => return Box::new(Unresolved(None)),
+            };
+
+            match value.downcast::<T>() {
+                Ok(value) => Box::new(f(*value, locator)),
+                Err(_) => Box::new(Unresolved(None)),
+            }
+        }));
+
+        self.insert_typed::<T>(key, provider);
+        true
+    }
+
+    /// Inserts a value of type `T` only if the condition is `true`,
+    /// returns `true` if the value was inserted.
+    pub fn insert_when<T>(&mut self, cond: bool, value: T) -> bool
+    where
+        T: Send + Sync + Clone + 'static,
+    {
+        if cond {
+            self.insert(value);
+        }
+
+        cond
+    }
+
+    /// Inserts a factory for a value of type `T` only if the condition is `true`,
+    /// returns `true` if the factory was inserted.
+    pub fn insert_with_when<F, T>(&mut self, cond: bool, factory: F) -> bool
+    where
+        F: Fn(&Self) -> T + 'static + Send + Sync,
+        T: Send + Sync + 'static,
+    {
+        if cond {
+            self.insert_with(factory);
+        }
+
+        cond
+    }
+
+    /// Inserts a value of type `T` into the `Locator` using an async factory function that takes a `Locator` as input.
+    ///
+    /// Values inserted with an async factory can only be retrieved using `get_async`, `get` returns `None` for them.
+    pub fn insert_async_with<F, Fut, T>(&mut self, factory: F) -> Option<Provider<S>>
+    where
+        F: Fn(&Self) -> Fut + 'static + Send + Sync,
+        Fut: Future<Output = T> + Send + 'static,
+        T: Send + Sync + 'static,
+    {
+        let provider = Provider::AsyncFactory(Arc::new(move |locator| {
+            let fut = factory(locator);
+            Box::pin(async move {
+                let value: Box<dyn Any + Send + Sync> = Box::new(fut.await);
+                value
+            })
+        }));
+
+        self.insert_typed::<T>(Key::of::<T>(), provider)
+    }
+
+    /// Inserts a value of type `T` using an async factory that only runs once, the value is cached
+    /// and cloned on each call to `get_async`.
+    ///
+    /// Async singletons can be created ahead of time using `initialize_all_async`.
+    pub fn insert_async_singleton_with<F, Fut, T>(&mut self, factory: F) -> Option<Provider<S>>
+    where
+        F: Fn(&Self) -> Fut + 'static + Send + Sync,
+        Fut: Future<Output = T> + Send + 'static,
+        T: Send + Sync + Clone + 'static,
+        S: Send + Sync,
+    {
+        let cache = Arc::new(OnceLock::<T>::new());
+        let provider = Provider::AsyncFactory(Arc::new(move |locator| {
+            let cache = cache.clone();
+            let fut = cache.get().is_none().then(|| factory(locator));
+            Box::pin(async move {
+                if let Some(fut) = fut {
+                    let value = fut.await;
+                    // If other caller initialized it first, its value is kept.
+                    let _ = cache.set(value);
+                }
+
+                let value = cache.get().expect("async singleton not initialized");
+                let value: Box<dyn Any + Send + Sync> = Box::new(value.clone());
+                value
+            })
+        }));
+
+        let key = Key::of::<T>();
+        let prev = self.insert_typed::<T>(key, provider);
+        if let Some(entry) = self.providers.get_mut(&key) {
+            entry.warm_up = Some(warm_up_provider::<T, S>);
+        }
+
+        prev
+    }
+
+    /// Creates all the async singletons of the locator concurrently, so later calls to `get_async`
+    /// return the cached values.
+    ///
+    /// No lock is held while the factories run, so singletons that depend on each other never deadlock,
+    /// if a dependency is resolved before its cached value is ready the first value stored is kept.
+    /// Async singletons of the parent locators are not initialized.
+    pub async fn initialize_all_async(&self) -> Result<(), Vec<LocatorError>>
+    where
+        S: Send + Sync,
+    {
+        let futures = self
+            .providers
+            .values()
+            .filter_map(|entry| Some((entry.warm_up?)(self, &entry.provider)))
+            .collect::<Vec<_>>();
+
+        let errors = join_all(futures).await;
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Inserts a value of type `T` shared behind an `Arc<T>`, the value itself is never cloned.
+    ///
+    /// The value is stored under the `Arc<T>` type and can be retrieved using `get_arc`.
+    pub fn insert_singleton<T>(&mut self, value: T) -> Option<Provider<S>>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.insert(Arc::new(value))
+    }
+
+    /// Inserts a value of type `T` shared behind an `Arc<T>` that is only kept alive by its users,
+    /// the locator holds a `Weak<T>` to the value.
+    ///
+    /// The value is created by the factory the first time is requested using `get_arc`, and created again
+    /// if all the `Arc<T>` returned were dropped. This is useful for large services that are rarely used.
+    pub fn insert_weak_with<F, T>(&mut self, factory: F) -> Option<Provider<S>>
+    where
+        F: Fn(&Self) -> T + 'static + Send + Sync,
+        T: Send + Sync + 'static,
+    {
+        let weak = Mutex::new(Weak::<T>::new());
+        self.insert_with(move |locator| {
+            let mut weak = weak.lock().unwrap_or_else(PoisonError::into_inner);
+            match weak.upgrade() {
+                Some(value) => value,
+                None => {
+                    let value = Arc::new(factory(locator));
+                    *weak = Arc::downgrade(&value);
+                    value
+                }
+            }
+        })
+    }
+
+    /// Returns a shared reference to a value of type `T` inserted using `insert_singleton` or `insert_weak_with`.
+    pub fn get_arc<T>(&self) -> Option<Arc<T>>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.get::<Arc<T>>()
+    }
+
+    /// Inserts a value of type `T` that can be mutated in place, the value is shared behind a `Mut<T>`.
+    ///
+    /// The value can be mutated using `invoke_scoped` or taking a `Mut<T>` as argument of `invoke`.
+    pub fn insert_mut<T>(&mut self, value: T) -> Option<Provider<S>>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.insert(Mut::new(value))
+    }
+
+    /// Inserts a value of type `T` created the first time it is used, the value is shared behind a `Lazy<T>`.
+    ///
+    /// Unlike `insert_cached_with`, resolving the service doesn't create the value,
+    /// so a function invoked with a `Lazy<T>` argument only creates it if it calls `Lazy::get`.
+    pub fn insert_lazy<T, F>(&mut self, init: F) -> Option<Provider<S>>
+    where
+        F: Fn() -> T + Send + Sync + 'static,
+        T: Send + Sync + 'static,
+    {
+        self.insert(Lazy::new(init))
+    }
+
+    /// Returns a value of type `T` from the `Locator` if it exists.
+    pub fn get<T>(&self) -> Option<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        let provider = self.unchecked_get(&TypeId::of::<T>())?;
+        self.resolve_provider(provider)
+    }
+
+    /// Returns the value of the service with the given `TypeId` without downcasting it,
+    /// for callers that only know the type at runtime. The caller downcasts the value to its type.
+    ///
+    /// Returns `None` if there is no such service or if it can only be resolved with `get_async`.
+    pub fn get_dyn(&self, id: TypeId) -> Option<Box<dyn Any + Send + Sync>> {
+        let entry = self.get_entry(&Key::unnamed(id))?;
+        let value = self.resolve_with(entry.type_name, &entry.provider, !entry.fallible, |value| {
+            if value.is::<Unresolved>() {
+                Err(value)
+            } else {
+                Ok(value)
+            }
+        });
+
+        value.ok()
+    }
+
+    /// Returns a value of type `T` from the `Locator` or the reason it cannot be resolved.
+    ///
+    /// Fails with `LocatorError::NotFound` if `T` is not registered and with `LocatorError::DowncastMismatch`
+    /// if the provider of `T` returns a value of other type, which can happen with `unchecked_insert`.
+    ///
+    /// When called from the factory of a service inserted by `try_insert_with`, the error is wrapped
+    /// in `LocatorError::WhileResolving`.
+    pub fn resolve<T>(&self) -> Result<T, LocatorError>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.resolve_direct::<T>().map_err(cycle::while_resolving)
+    }
+
+    /// Resolves a value of type `T` the same as `resolve`, without wrapping the error
+    /// in `LocatorError::WhileResolving`.
+    pub(crate) fn resolve_direct<T>(&self) -> Result<T, LocatorError>
+    where
+        T: Send + Sync + 'static,
+    {
+        let Some(entry) = self.get_entry(&Key::of::<T>()) else {
+            return Err(self.not_found::<T>());
+        };
+
+        match self.resolve_value::<T>(&entry.provider) {
+            Ok(value) => Ok(value),
+            Err(Some(value)) => match value.downcast::<Unresolved>() {
+                Ok(unresolved) => Err(unresolved.0.unwrap_or_else(|| self.not_found::<T>())),
+                Err(_) => Err(LocatorError::DowncastMismatch {
+                    expected: type_name::<T>(),
+                    found: entry.type_name,
+                }),
+            },
+            Err(None) => Err(self.not_found::<T>()),
+        }
+    }
+
+    /// Resolves several services at once, for example `let (a, b): (A, B) = locator.get_many()?`,
+    /// fails with the error of the first service that cannot be resolved.
+    pub fn get_many<T>(&self) -> Result<T, LocatorError>
+    where
+        T: FromLocator<S>,
+    {
+        T::from_locator(self)
+    }
+
+    /// Returns a value of type `T` from the `Locator` or `T::default()` if is not in the locator,
+    /// the default value is not inserted.
+    pub fn get_or_default<T>(&self) -> T
+    where
+        T: Send + Sync + Default + 'static,
+    {
+        self.get::<T>().unwrap_or_default()
+    }
+
+    /// Returns a value of type `T` from the `Locator` or the given value if is not in the locator,
+    /// the given value is not inserted.
+    pub fn get_or<T>(&self, default: T) -> T
+    where
+        T: Send + Sync + 'static,
+    {
+        self.get::<T>().unwrap_or(default)
+    }
+
+    /// Returns a value of type `T` from the `Locator` or the value returned by `f` if is not in the locator,
+    /// the value is not inserted and `f` only runs if the value is missing.
+    pub fn get_or_else<T, F>(&self, f: F) -> T
+    where
+        T: Send + Sync + 'static,
+        F: FnOnce() -> T,
+    {
+        self.get::<T>().unwrap_or_else(f)
+    }
+
+    /// Returns a value of type `T` from the `Locator` awaiting its factory if was inserted using `insert_async_with`.
+    pub async fn get_async<T>(&self) -> Option<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        let provider = self.unchecked_get(&TypeId::of::<T>())?;
+
+        match provider {
+            Provider::AsyncFactory(f) => {
+                let start = self.metrics.is_some().then(Instant::now);
+                let value = trace::resolve_async::<T, _>(f(self)).await;
+                let value = value.downcast::<T>().map(|x| *x).ok();
+                if value.is_some() {
+                    self.record(type_name::<T>(), start);
+                }
+
+                value
+            }
+            _ => self.resolve_provider(provider),
+        }
+    }
+
+    /// Returns a value of type `T` awaiting its factory for at most the given duration,
+    /// fails with `LocatorError::Timeout` if the factory takes longer.
+    ///
+    /// The time is awaited by a thread started for this call so it works with any runtime,
+    /// the thread stops when the call completes. Use `get_async_timeout_with` to use the timer of the runtime instead.
+    pub async fn get_async_timeout<T>(&self, timeout: Duration) -> Result<T, LocatorError>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.get_async_timeout_with(timeout, timer::sleep).await
+    }
+
+    /// Returns a value of type `T` awaiting its factory until the future returned by `sleep` completes,
+    /// for example `locator.get_async_timeout_with(duration, tokio::time::sleep)`.
+    pub async fn get_async_timeout_with<T, F, Fut>(
+        &self,
+        timeout: Duration,
+        sleep: F,
+    ) -> Result<T, LocatorError>
+    where
+        T: Send + Sync + 'static,
+        F: FnOnce(Duration) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        if !self.contains::<T>() {
+            return Err(self.not_found::<T>());
+        }
+
+        let start = Instant::now();
+        let mut value = pin!(self.get_async::<T>());
+        let mut timer = pin!(sleep(timeout));
+
+        std::future::poll_fn(|cx| {
+            if let Poll::Ready(value) = value.as_mut().poll(cx) {
+                return Poll::Ready(value.ok_or_else(LocatorError::not_found::<T>));
+            }
+
+            timer.as_mut().poll(cx).map(|()| {
+                Err(LocatorError::Timeout {
+                    expected: type_name::<T>(),
+                    elapsed: start.elapsed(),
+                })
+            })
+        })
+        .await
+    }
+
+    /// Returns the value of type `T` or inserts the value returned by the given function if the type is not in the locator.
+    ///
+    /// If `T` is registered using a factory, the factory is used to resolve the value and is not replaced.
+    pub fn get_or_insert_with<T, F>(&mut self, f: F) -> T
+    where
+        F: FnOnce(&Self) -> T,
+        T: Send + Sync + Clone + 'static,
+    {
+        if let Some(value) = self.get::<T>() {
+            return value;
+        }
+
+        let value = f(self);
+        self.insert(value.clone());
+        value
+    }
+
+    /// Returns the entry of the service of type `T` for in-place registration,
+    /// the services of the parent locators are not considered.
+    pub fn entry<T>(&mut self) -> ServiceEntry<'_, T, S>
+    where
+        T: Send + Sync + 'static,
+    {
+        match self.providers.entry(Key::of::<T>()) {
+            hash_map::Entry::Occupied(inner) => ServiceEntry::Occupied(OccupiedEntry {
+                inner,
+                _marker: PhantomData,
+            }),
+            hash_map::Entry::Vacant(inner) => ServiceEntry::Vacant(VacantEntry {
+                inner,
+                _marker: PhantomData,
+            }),
+        }
+    }
+
+    /// Returns a reference to the value of type `T` from the `Locator` without cloning it.
+    ///
+    /// Only values inserted as a single instance or with `insert_cached_with` can be borrowed,
+    /// this returns `None` for values created by other factories.
+    pub fn get_ref<T>(&self) -> Option<&T>
+    where
+        T: Send + Sync + 'static,
+    {
+        match self.unchecked_get(&TypeId::of::<T>())? {
+            Provider::Single(singleton) => singleton.get_ref().downcast_ref::<T>(),
+            Provider::Cached(cached) => {
+                cycle::guard_provider(cached.addr(), type_name::<T>(), || cached.get_ref(self))
+                    .ok()?
+                    .downcast_ref::<T>()
+            }
+            Provider::Factory(_) | Provider::AsyncFactory(_) => None,
+        }
+    }
+
+    /// Inserts a value of type `T` with the given name, named values don't replace the unnamed ones.
+    pub fn insert_named<T>(&mut self, name: &'static str, value: T) -> Option<Provider<S>>
+    where
+        T: Send + Sync + Clone + 'static,
+    {
+        let provider = Provider::Single(Singleton::from_value(value));
+        self.insert_typed::<T>(Key::named::<T>(name), provider)
+    }
+
+    /// Inserts a value of type `T` with the given name using a factory function that takes a `Locator` as input.
+    pub fn insert_with_named<F, T>(&mut self, name: &'static str, factory: F) -> Option<Provider<S>>
+    where
+        F: Fn(&Self) -> T + 'static + Send + Sync,
+        T: Send + Sync + 'static,
+    {
+        let provider = Provider::Factory(Arc::new(move |locator| {
+            let value = factory(locator);
+            Box::new(value)
+        }));
+
+        self.insert_typed::<T>(Key::named::<T>(name), provider)
+    }
+
+    /// Returns the value of type `T` with the given name if it exists.
+    pub fn get_named<T>(&self, name: &'static str) -> Option<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        let provider = self.get_provider(&Key::named::<T>(name))?;
+        self.resolve_provider(provider)
+    }
+
+    /// Returns a boolean indicating whether a value of type `T` with the given name exists in the `Locator` or any of its parents.
+    pub fn contains_named<T>(&self, name: &'static str) -> bool
+    where
+        T: Send + Sync + 'static,
+    {
+        self.get_provider(&Key::named::<T>(name)).is_some()
+    }
+
+    /// Removes the value of type `T` with the given name from the `Locator` if it exists.
+    ///
+    /// The unnamed value and the values with other names are kept, the parent locators are not modified.
+    pub fn remove_named<T>(&mut self, name: &'static str) -> Option<Provider<S>>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.providers
+            .remove(&Key::named::<T>(name))
+            .map(|e| e.provider)
+    }
+
+    /// Adds a value of type `T` to the values returned by `get_all`.
+    ///
+    /// These values are stored separately from the ones added with `insert` and are not returned by `get`.
+    pub fn register_many<T>(&mut self, value: T)
+    where
+        T: Send + Sync + Clone + 'static,
+    {
+        let provider = Provider::Single(Singleton::from_value(value));
+        self.multi
+            .entry(TypeId::of::<T>())
+            .or_default()
+            .push(provider);
+    }
+
+    /// Adds a factory for a value of type `T` to the values returned by `get_all`.
+    ///
+    /// These values are stored separately from the ones added with `insert_with` and are not returned by `get`.
+    pub fn register_many_with<F, T>(&mut self, factory: F)
+    where
+        F: Fn(&Self) -> T + 'static + Send + Sync,
+        T: Send + Sync + 'static,
+    {
+        let provider = Provider::Factory(Arc::new(move |locator| {
+            let value = factory(locator);
+            Box::new(value)
+        }));
+
+        self.multi
+            .entry(TypeId::of::<T>())
+            .or_default()
+            .push(provider);
+    }
+
+    /// Returns all the values of type `T` added with `register_many` in insertion order,
+    /// the values of the parent locators are returned first.
+    pub fn get_all<T>(&self) -> Vec<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        let mut values = match &self.parent {
+            Some(parent) => parent.get_all::<T>(),
+            None => Vec::new(),
+        };
+
+        if let Some(providers) = self.multi.get(&TypeId::of::<T>()) {
+            values.extend(
+                providers
+                    .iter()
+                    .filter_map(|p| self.resolve_provider::<T>(p)),
+            );
+        }
+
+        values
+    }
+
+    /// Calls `f` with each value of type `T` returned by `get_all` and returns the results in the same order.
+    pub fn invoke_all<T, F, R>(&self, f: F) -> Vec<R>
+    where
+        T: Send + Sync + 'static,
+        F: FnMut(T) -> R,
+    {
+        self.get_all::<T>().into_iter().map(f).collect()
+    }
+
+    /// Adds a factory for an implementor of the trait object `D` to the values returned by `resolve_impls`.
+    ///
+    /// The implementors are stored by the `TypeId` of `Box<D>`, so `D` is usually
+    /// a `dyn Trait + Send + Sync`, see also the `register_impl!` macro.
+    pub fn register_impl<D, F>(&mut self, factory: F)
+    where
+        D: ?Sized + Send + Sync + 'static,
+        F: Fn(&Self) -> Box<D> + 'static + Send + Sync,
+    {
+        self.register_many_with(factory);
+    }
+
+    /// Returns all the implementors of the trait object `D` added with `register_impl` in insertion order.
+    pub fn resolve_impls<D>(&self) -> Vec<Box<D>>
+    where
+        D: ?Sized + Send + Sync + 'static,
+    {
+        self.get_all::<Box<D>>()
+    }
+
+    /// Returns a boolean indicating whether a value of type `T` exists in the `Locator` or any of its parents.
+    pub fn contains<T>(&self) -> bool
+    where
+        T: Send + Sync + 'static,
+    {
+        self.unchecked_get(&TypeId::of::<T>()).is_some()
+    }
+
+    /// Returns a boolean indicating whether all the services of the tuple `T` exist in the `Locator`
+    /// or any of its parents, for example `locator.contains_all::<(Config, Database)>()`.
+    ///
+    /// The services are not resolved, so this is a cheap check before calling `invoke`.
+    pub fn contains_all<T>(&self) -> bool
+    where
+        T: FromLocator<S>,
+    {
+        T::contained_in(self)
+    }
+
+    /// Returns a boolean indicating whether a service with the given `TypeId` exists in the `Locator`
+    /// or any of its parents.
+    pub fn contains_type_id(&self, id: TypeId) -> bool {
+        self.unchecked_get(&id).is_some()
+    }
+
+    /// Removes a value of type `T` from the `Locator` if it exists.
+    ///
+    /// Only the unnamed value is removed, use `remove_named` to remove the values inserted with a name.
+    pub fn remove<T>(&mut self) -> Option<Provider<S>>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.providers.remove(&Key::of::<T>()).map(|e| e.provider)
+    }
+
+    /// Removes a value of type `T` from the `Locator` and returns it,
+    /// if the value was created by a factory the factory is called one last time.
+    ///
+    /// A value inserted with `insert` is moved out of the locator, it is only cloned
+    /// if it is shared with a clone of this locator.
+    pub fn take<T>(&mut self) -> Option<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        match self.remove::<T>()? {
+            Provider::Single(singleton) => singleton.into_value().downcast::<T>().ok().map(|x| *x),
+            provider => self.resolve_provider(&provider),
+        }
+    }
+
+    /// Removes the service of type `T` from this locator only if it can be resolved,
+    /// a value inserted with `insert` is moved out the same as `take`.
+    fn take_resolved<T>(&mut self) -> Option<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        match &self.providers.get(&Key::of::<T>())?.provider {
+            Provider::Single(singleton) if singleton.get_ref().is::<T>() => self.take::<T>(),
+            provider => {
+                let value = self.resolve_provider::<T>(provider)?;
+                self.remove::<T>();
+                Some(value)
+            }
+        }
+    }
+
+    /// Removes the service of type `T` from the `Locator` and invokes the given function with it,
+    /// the value is moved into the function instead of cloned, see `take`.
+    ///
+    /// Useful for one-shot initialization that consumes a service, fails if there is no service of type `T`
+    /// or if it cannot be resolved, in which case the service is kept.
+    pub fn take_invoke<T, F, R>(&mut self, f: F) -> Result<R, LocatorError>
+    where
+        F: FnOnce(T) -> R,
+        T: Send + Sync + 'static,
+    {
+        match self.take_resolved::<T>() {
+            Some(value) => Ok(f(value)),
+            None => Err(self.not_found::<T>()),
+        }
+    }
+
+    /// Inserts a value of type `T` and returns the value it replaced,
+    /// if the previous value was created by a factory the factory is called one last time.
+    ///
+    /// A previous value inserted with `insert` is moved out the same as `take`.
+    pub fn replace<T>(&mut self, value: T) -> Option<T>
+    where
+        T: Send + Sync + Clone + 'static,
+    {
+        match self.insert(value)? {
+            Provider::Single(singleton) => singleton.into_value().downcast::<T>().ok().map(|x| *x),
+            provider => self.resolve_provider(&provider),
+        }
+    }
+
+    /// Replaces the factory of the service of type `T` and returns the previous provider,
+    /// the value cached for the previous factory is discarded so the next resolution uses the new factory.
+    ///
+    /// The new factory keeps the lifetime of the previous service, see `register`. The services inserted
+    /// as a value or with `insert_cached_with` are `Lifetime::Singleton`, the ones registered with
+    /// `Lifetime::Scoped` stay scoped and any other factory is `Lifetime::Transient`.
+    pub fn replace_with<T, F>(&mut self, factory: F) -> Option<Provider<S>>
+    where
+        F: Fn(&Self) -> T + 'static + Send + Sync,
+        T: Send + Sync + Clone + 'static,
+    {
+        let lifetime = match self.providers.get(&Key::of::<T>()) {
+            Some(entry) if entry.scoped => Lifetime::Scoped,
+            Some(entry) if matches!(entry.provider, Provider::Single(_) | Provider::Cached(_)) => {
+                Lifetime::Singleton
+            }
+            _ => Lifetime::Transient,
+        };
+
+        self.register(lifetime, factory)
+    }
+
+    /// Returns the number of registrations in the locator, excluding the ones of its parents.
+    ///
+    /// Each named service and each service registered with `register_many` counts as one,
+    /// use `type_count` for the number of different types.
+    pub fn len(&self) -> usize {
+        self.providers.len() + self.multi.values().map(Vec::len).sum::<usize>()
+    }
+
+    /// Returns the number of different types registered in the locator, excluding the ones of its parents.
+    ///
+    /// A type registered unnamed, with names and with `register_many` counts once.
+    pub fn type_count(&self) -> usize {
+        let mut ids = self
+            .providers
+            .keys()
+            .map(|key| key.type_id)
+            .chain(self.multi.keys().copied())
+            .collect::<Vec<_>>();
+
+        ids.sort_unstable();
+        ids.dedup();
+        ids.len()
+    }
+
+    /// Returns `true` if the locator has no registrations, excluding the ones of its parents.
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty() && self.multi.values().all(Vec::is_empty)
+    }
+
+    /// Removes all the services from the locator, the parent locators are not modified.
+    pub fn clear(&mut self) {
+        self.providers.clear();
+        self.multi.clear();
+        self.scoped_values().clear();
+    }
+
+    /// Keeps only the services which type matches the predicate, the parent locators are not modified.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&TypeId) -> bool,
+    {
+        self.providers.retain(|key, _| f(&key.type_id));
+        self.multi.retain(|id, _| f(id));
+        self.scoped_values().retain(|id, _| f(id));
+    }
+
+    /// Returns the `TypeId` of the services in the locator, excluding the ones of its parents.
+    ///
+    /// Services inserted with a name are not included, they are not resolved by `get`.
+    pub fn keys(&self) -> impl Iterator<Item = TypeId> + '_ {
+        self.providers
+            .keys()
+            .filter(|key| key.name.is_none())
+            .map(|key| key.type_id)
+    }
+
+    /// Returns the `TypeId` of the unnamed services in the locator and its parents.
+    pub(crate) fn resolvable_keys(&self) -> Vec<TypeId> {
+        let mut keys = self.keys().collect::<Vec<_>>();
+        if let Some(parent) = &self.parent {
+            keys.extend(parent.resolvable_keys());
+        }
+
+        keys
+    }
+
+    /// Returns the type names of the services in the locator, excluding the ones of its parents.
+    pub fn type_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.providers.values().map(|entry| entry.type_name)
+    }
+
+    /// Returns the type name and the kind of provider of the services in the locator,
+    /// excluding the ones of its parents, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, ProviderKind)> + '_ {
+        self.providers
+            .values()
+            .map(|entry| (entry.type_name, entry.provider.kind()))
+    }
+
+    /// Returns the kind of provider of the service of type `T`, including the ones of its parents,
+    /// or `None` if the service is not registered.
+    pub fn provider_kind<T>(&self) -> Option<ProviderKind>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.get_provider(&Key::of::<T>()).map(Provider::kind)
+    }
+
+    /// Returns the type names of the registered services sorted, excluding the ones of its parents.
+    ///
+    /// Services inserted with a name are listed once for each name.
+    pub fn registered(&self) -> Vec<String> {
+        self.info()
+            .services
+            .into_iter()
+            .map(|service| service.type_name.to_owned())
+            .collect()
+    }
+
+    /// Returns the services registered in the locator, excluding the ones of its parents
+    /// and the ones registered with `register_many`.
+    pub fn info(&self) -> LocatorInfo {
+        let mut services = self
+            .providers
+            .iter()
+            .map(|(key, entry)| ServiceInfo {
+                type_name: entry.type_name,
+                name: key.name,
+                kind: entry.provider.kind(),
+            })
+            .collect::<Vec<_>>();
+
+        services.sort_by_key(|service| (service.type_name, service.name));
+        LocatorInfo { services }
+    }
+
+    /// Returns a not found error for `T` suggesting the registered services with a similar name,
+    /// including the ones of the parents.
+    pub fn not_found<T: 'static>(&self) -> LocatorError {
+        let expected = type_name::<T>();
+        let mut candidates = Vec::new();
+        let mut locator = Some(self);
+
+        while let Some(current) = locator {
+            candidates.extend(current.type_names());
+            locator = current.parent.as_deref();
+        }
+
+        LocatorError::NotFound {
+            expected,
+            suggestions: crate::error::similar_type_names(expected, candidates),
+        }
+    }
+
+    /// Resolves every service in the locator once and returns all the errors found,
+    /// this allows to catch misconfigured services at startup instead of the first time they are used.
+    ///
+    /// Services of the parent locators, services inserted using `unchecked_insert`
+    /// and services resolved asynchronously are not checked. Services that require themselves
+    /// fail with `LocatorError::Cyclic`.
+    pub fn verify(&self) -> Result<(), Vec<LocatorError>> {
+        let errors = self
+            .providers
+            .values()
+            .filter_map(|entry| {
+                let verify = entry.verify?;
+                verify(self, &entry.provider).err()
+            })
+            .collect::<Vec<_>>();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Returns the dependency graph of the services in the locator in Graphviz DOT format.
+    ///
+    /// The dependencies are discovered by resolving each service once, like `verify`, and recording
+    /// the services requested by its factory. Services of the parents are only included if requested.
+    pub fn to_dot(&self) -> String {
+        let mut edges = graph::track(|| {
+            for entry in self.providers.values() {
+                if let Some(verify) = entry.verify {
+                    let _tracking = graph::enter(entry.type_name);
+                    let _ = verify(self, &entry.provider);
+                }
+            }
+        });
+
+        let mut nodes = self.type_names().collect::<Vec<_>>();
+        nodes.sort_unstable();
+        nodes.dedup();
+        edges.sort_unstable();
+        edges.dedup();
+
+        let mut dot = String::from("digraph {\n");
+        for node in nodes {
+            dot.push_str(&format!("    \"{node}\";\n"));
+        }
+
+        for (from, to) in edges {
+            dot.push_str(&format!("    \"{from}\" -> \"{to}\";\n"));
+        }
+
+        dot.push('}');
+        dot
+    }
+
+    /// Captures the services of the locator so they can be restored later using `restore`.
+    ///
+    /// The providers and the values with `Lifetime::Scoped` are shared with the checkpoint,
+    /// so singletons and scoped services keep their values.
+    pub fn checkpoint(&self) -> Checkpoint<S>
+    where
+        S: Clone,
+    {
+        Checkpoint {
+            providers: self.providers.clone(),
+            multi: self.multi.clone(),
+            scoped: self
+                .scoped
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone(),
+        }
+    }
+
+    /// Reverts the services of the locator to the given checkpoint,
+    /// the services inserted or removed after the checkpoint was taken are discarded.
+    ///
+    /// The values with `Lifetime::Scoped` are also reverted, the ones created after the checkpoint
+    /// are created again when requested.
+    pub fn restore(&mut self, checkpoint: Checkpoint<S>) {
+        self.providers = checkpoint.providers;
+        self.multi = checkpoint.multi;
+        *self.scoped_values() = checkpoint.scoped;
+    }
+
+    /// Copies the registration of the service of type `T` into the given locator, including the ones
+    /// of its parents, returns `false` if there is no service of type `T`.
+    ///
+    /// Both locators can be modified independently after the copy,
+    /// a value inserted with `insert` is created once and shared by both.
+    pub fn clone_into<T>(&self, dest: &mut Locator<S>) -> bool
+    where
+        T: Send + Sync + 'static,
+    {
+        let key = Key::of::<T>();
+        match self.get_entry(&key) {
+            Some(entry) => {
+                dest.providers.insert(key, entry.clone());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Registers a function to run when the locator is shut down, see `Locator::shutdown`.
+    pub fn on_shutdown<F>(&mut self, f: F)
+    where
+        F: FnOnce(&Locator<S>) + Send + Sync + 'static,
+    {
+        self.shutdown_hooks.push(Box::new(f));
+    }
+
+    /// Runs the functions registered with `on_shutdown` in reverse registration order and drops the locator.
+    ///
+    /// The hooks are not run if the locator is dropped without calling this method.
+    pub fn shutdown(mut self) {
+        let hooks = std::mem::take(&mut self.shutdown_hooks);
+        for hook in hooks.into_iter().rev() {
+            hook(&self);
+        }
+    }
+
+    /// Registers the services of the given module.
+    pub fn install<M>(&mut self, module: M) -> &mut Self
+    where
+        M: Module<S>,
+    {
+        module.register(self);
+        self
+    }
+
+    /// Adds the providers from other locator, replacing the existing ones.
+    ///
+    /// This is equivalent to `extend_with(other, MergeStrategy::Overwrite)`.
+    pub fn extend(&mut self, other: Self) {
+        self.extend_with(other, MergeStrategy::Overwrite)
+            .expect("overwrite merge cannot fail");
+    }
+
+    /// Adds the providers from other locator resolving the conflicts with the given strategy.
+    ///
+    /// With `MergeStrategy::Error` nothing is added if any service is registered in both locators,
+    /// the error names the first of those types sorted by name.
+    /// Services registered with `register_many` never conflict, they are appended.
+    ///
+    /// The `on_shutdown` hooks of other locator are appended to the hooks of this one,
+    /// so `shutdown` runs them before the hooks already in this locator.
+    pub fn extend_with(
+        &mut self,
+        other: Self,
+        strategy: MergeStrategy,
+    ) -> Result<(), LocatorError> {
+        if strategy == MergeStrategy::Error {
+            // The first type by name, so the error doesn't depend on the iteration order.
+            let conflict = other
+                .providers
+                .iter()
+                .filter(|(key, _)| self.providers.contains_key(key))
+                .map(|(_, entry)| entry.type_name)
+                .min();
+
+            if let Some(type_name) = conflict {
+                return Err(LocatorError::AlreadyRegistered { type_name });
+            }
+        }
+
+        for (key, entry) in other.providers {
+            match strategy {
+                MergeStrategy::KeepExisting => {
+                    self.providers.entry(key).or_insert(entry);
+                }
+                MergeStrategy::Overwrite | MergeStrategy::Error => {
+                    self.providers.insert(key, entry);
+                }
+            }
+        }
+
+        for (id, providers) in other.multi {
+            self.multi.entry(id).or_default().extend(providers);
+        }
+
+        self.shutdown_hooks.extend(other.shutdown_hooks);
+
+        Ok(())
+    }
+
+    /// Adds the providers from other locator resolving the conflicts of the services inserted with
+    /// `try_insert_with` or `try_insert_async_with` with the given strategy, other services are replaced.
+    ///
+    /// Returns the type names of the fallible services registered in both locators. With `MergeStrategy::Error`
+    /// nothing is added and `LocatorError::AlreadyRegistered` is returned if there is any.
+    ///
+    /// The `on_shutdown` hooks are merged the same as `extend_with`.
+    pub fn try_extend(
+        &mut self,
+        other: Self,
+        strategy: MergeStrategy,
+    ) -> Result<Vec<&'static str>, LocatorError> {
+        let is_conflict = |providers: &HashMap<Key, Entry<S>, S>, key: &Key, entry: &Entry<S>| {
+            entry.fallible && providers.get(key).is_some_and(|e| e.fallible)
+        };
+
+        let mut conflicts = other
+            .providers
+            .iter()
+            .filter(|(key, entry)| is_conflict(&self.providers, key, entry))
+            .map(|(_, entry)| entry.type_name)
+            .collect::<Vec<_>>();
+
+        conflicts.sort();
+
+        if let (MergeStrategy::Error, Some(type_name)) = (strategy, conflicts.first()) {
+            return Err(LocatorError::AlreadyRegistered { type_name });
+        }
+
+        let keep_existing = strategy == MergeStrategy::KeepExisting;
+        for (key, entry) in other.providers {
+            if keep_existing && is_conflict(&self.providers, &key, &entry) {
+                continue;
+            }
+
+            self.providers.insert(key, entry);
+        }
+
+        for (id, providers) in other.multi {
+            self.multi.entry(id).or_default().extend(providers);
+        }
+
+        self.shutdown_hooks.extend(other.shutdown_hooks);
+
+        Ok(conflicts)
+    }
+
+    /// Invoke the given function injecting the dependencies from this locator.
+    ///
+    /// To receive the locator itself or an optional service use `invoke_ref`
+    /// with an argument of type `LocatorRef` or `Option<T>`.
+    pub fn invoke<F, Args>(&self, f: F) -> Result<F::Output, LocatorError>
+    where
+        F: Invoke<Args>,
+        Args: FromLocator<S>,
+    {
+        let args = Args::from_locator(self)?;
+        Ok(Invoke::call(f, args))
+    }
+
+    /// Invoke the given function injecting the dependencies from this locator, checking first that all the
+    /// dependencies exist.
+    ///
+    /// Fails with `LocatorError::Missing` containing all the missing dependencies instead of only the first one,
+    /// other errors are returned the same as `invoke`.
+    pub fn invoke_checked<F, Args>(&self, f: F) -> Result<F::Output, LocatorError>
+    where
+        F: Invoke<Args>,
+        Args: FromLocator<S>,
+    {
+        let types = Args::missing_in(self);
+        if !types.is_empty() {
+            return Err(LocatorError::Missing { types });
+        }
+
+        self.invoke(f)
+    }
+
+    /// Invoke the given function passing references to the services of this locator,
+    /// for example `|config: &Config, pool: &Pool| ...`.
+    ///
+    /// Only the services inserted as a value or with `insert_cached_with` can be borrowed,
+    /// services inserted with other factories fail to resolve.
+    /// Arguments of type `Option<T>` receive `get::<T>()`, so any service can be resolved as optional.
+    ///
+    /// An argument of type `LocatorRef` receives the locator itself to resolve any other service.
+    pub fn invoke_ref<'a, F, Args>(&'a self, f: F) -> Result<F::Output, LocatorError>
+    where
+        F: Invoke<Args>,
+        Args: FromLocatorRef<'a, S>,
+    {
+        let args = Args::from_locator_ref(self)?;
+        Ok(Invoke::call(f, args))
+    }
+
+    /// Invoke the given async function injecting the dependencies from this locator.
+    pub async fn invoke_async<F, Fut, Args>(&self, f: F) -> Result<Fut::Output, LocatorError>
+    where
+        F: AsyncInvoke<Args, Fut = Fut>,
+        Fut: Future,
+        Args: FromLocator<S>,
+    {
+        let args = Args::from_locator(self)?;
+        Ok(AsyncInvoke::call(f, args).await)
+    }
+
+    /// Invoke the given function with a mutable reference to a value inserted using `insert_mut`,
+    /// the value is locked for writing while the function runs.
+    pub fn invoke_scoped<T, F, R>(&self, f: F) -> Result<R, LocatorError>
+    where
+        F: FnOnce(&mut T) -> R,
+        T: Send + Sync + 'static,
+    {
+        let value = self
+            .get::<Mut<T>>()
+            .ok_or_else(|| self.not_found::<Mut<T>>())?;
+
+        let mut guard = value.write();
+        Ok(f(&mut guard))
+    }
+
+    /// Invoke the given fallible function injecting the dependencies from this locator,
+    /// the error of the function is converted into a `LocatorError`.
+    ///
+    /// The arguments are resolved using `try_get`, so the services inserted with `try_insert_with`
+    /// are injected as `T` and the call fails with their error if they cannot be created.
+    pub fn try_invoke<F, Args, T, E>(&self, f: F) -> Result<T, LocatorError>
+    where
+        F: Invoke<Args, Output = Result<T, E>>,
+        Args: FromLocator<S>,
+        E: Into<LocatorError>,
+    {
+        let args = Args::try_from_locator(self)?;
+        Invoke::call(f, args).map_err(Into::into)
+    }
+
+    /// Invoke the given fallible async function injecting the dependencies from this locator,
+    /// the error of the function is converted into a `LocatorError`.
+    ///
+    /// The arguments are resolved the same as `try_invoke`.
+    pub async fn try_invoke_async<F, Fut, Args, T, E>(&self, f: F) -> Result<T, LocatorError>
+    where
+        F: AsyncInvoke<Args, Fut = Fut>,
+        Fut: Future<Output = Result<T, E>>,
+        Args: FromLocator<S>,
+        E: Into<LocatorError>,
+    {
+        let args = Args::try_from_locator(self)?;
+        AsyncInvoke::call(f, args).await.map_err(Into::into)
+    }
+}
+
+/// Inserts the providers without checking their types, see `Locator::unchecked_insert`.
+///
+/// If a `TypeId` is already in the locator or repeated in the iterator, the last provider replaces the others.
+///
+/// Call it as `Extend::extend(&mut locator, iter)`, because `Locator::extend` merges another locator.
+impl<S: BuildHasher> Extend<(TypeId, Provider<S>)> for Locator<S> {
+    fn extend<I: IntoIterator<Item = (TypeId, Provider<S>)>>(&mut self, iter: I) {
+        for (id, provider) in iter {
+            self.unchecked_insert(id, provider);
+        }
+    }
+}
+
+/// Creates a locator with the providers without checking their types, see `Locator::unchecked_insert`.
+///
+/// If a `TypeId` is repeated in the iterator, the last provider replaces the others.
+impl<S: BuildHasher + Default> FromIterator<(TypeId, Provider<S>)> for Locator<S> {
+    fn from_iter<I: IntoIterator<Item = (TypeId, Provider<S>)>>(iter: I) -> Self {
+        let mut locator = Locator::default();
+        Extend::extend(&mut locator, iter);
+        locator
+    }
+}
+
+/// Consumes the locator returning its providers, the iterator of `Locator::into_iter`.
+///
+/// Only the unnamed services are returned, the services inserted with a name
+/// or registered with `register_many` are dropped.
+pub struct IntoIter<S = RandomState> {
+    inner: std::collections::hash_map::IntoIter<Key, Entry<S>>,
+}
+
+impl<S> Iterator for IntoIter<S> {
+    type Item = (TypeId, Provider<S>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .by_ref()
+            .find(|(key, _)| key.name.is_none())
+            .map(|(key, entry)| (key.type_id, entry.provider))
+    }
+}
+
+/// Consumes the locator returning the `TypeId` and the provider of each unnamed service,
+/// excluding the ones of its parents. It can be collected back into a `Locator`.
+impl<S> IntoIterator for Locator<S> {
+    type Item = (TypeId, Provider<S>);
+    type IntoIter = IntoIter<S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            inner: self.providers.into_iter(),
+        }
+    }
+}
+
+impl<S> Debug for Locator<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut services = self
+            .providers
+            .iter()
+            .map(|(key, entry)| match key.name {
+                Some(name) => format!("{} ({name:?})", entry.type_name),
+                None => entry.type_name.to_owned(),
+            })
+            .collect::<Vec<_>>();
+
+        services.sort();
+
+        f.debug_struct("Locator")
+            .field("services", &services)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct MyStruct {
+        val: i32,
+    }
+
+    #[test]
+    fn test_insert_single() {
+        let mut locator = Locator::new();
+
+        assert!(locator.insert(MyStruct { val: 42 }).is_none());
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 42);
+    }
+
+    #[test]
+    fn test_insert_single_is_cached() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static CLONE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        struct Counted;
+        impl Clone for Counted {
+            fn clone(&self) -> Self {
+                CLONE_COUNT.fetch_add(1, Ordering::SeqCst);
+                Counted
+            }
+        }
+
+        let mut locator = Locator::new();
+        locator.insert(Counted);
+
+        for _ in 0..10 {
+            assert!(locator.get::<Counted>().is_some());
+        }
+
+        // The inserted value is stored once, every `get` clones it and nothing else.
+        assert_eq!(CLONE_COUNT.load(Ordering::SeqCst), 10);
+    }
+
+    #[test]
+    fn test_insert_single_concurrent_init() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        for _ in 0..10 {
+            let init_count = Arc::new(AtomicUsize::new(0));
+            let mut locator = Locator::new();
+            locator.unchecked_insert(
+                TypeId::of::<MyStruct>(),
+                Provider::Single(Singleton::new({
+                    let init_count = init_count.clone();
+                    move || MyStruct {
+                        val: init_count.fetch_add(1, Ordering::SeqCst) as i32 + 42,
+                    }
+                })),
+            );
+
+            let values = std::thread::scope(|scope| {
+                let handles = (0..32)
+                    .map(|_| scope.spawn(|| locator.get::<MyStruct>().unwrap()))
+                    .collect::<Vec<_>>();
+
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().unwrap())
+                    .collect::<Vec<_>>()
+            });
+
+            assert_eq!(init_count.load(Ordering::SeqCst), 1);
+            assert!(values.iter().all(|value| value.val == 42));
+        }
+    }
+
+    #[test]
+    fn test_factory_reads_cached_singleton() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static CLONE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        struct Pool;
+        impl Clone for Pool {
+            fn clone(&self) -> Self {
+                CLONE_COUNT.fetch_add(1, Ordering::SeqCst);
+                Pool
+            }
+        }
+
+        let mut locator = Locator::new();
+        locator.insert(Pool);
+        locator.insert_cached_with(|locator| {
+            locator.get::<Pool>().unwrap();
+            MyStruct { val: 42 }
+        });
+
+        for _ in 0..10 {
+            assert_eq!(locator.get::<MyStruct>().unwrap().val, 42);
+        }
+
+        // The inserted value is not cloned until requested, and the factory runs once.
+        assert_eq!(CLONE_COUNT.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_with_capacity() {
+        let mut locator = Locator::with_capacity(16);
+        assert!(locator.is_empty());
+
+        locator.insert(MyStruct { val: 42 });
+        locator.insert_with(|_| String::from("hello"));
+
+        assert_eq!(locator.len(), 2);
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 42);
+        assert_eq!(locator.get::<String>().unwrap(), "hello");
+    }
+
+    #[test]
+    fn test_insert_with_factory() {
+        let mut locator = Locator::new();
+
+        locator.insert_with::<_, MyStruct>(|_| MyStruct { val: 42 });
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 42);
+    }
+
+    #[test]
+    fn test_insert_if_absent() {
+        let mut locator = Locator::new();
+
+        assert!(locator.insert_if_absent(MyStruct { val: 1 }));
+        assert!(!locator.insert_if_absent(MyStruct { val: 2 }));
+        assert!(!locator.insert_with_if_absent(|_| MyStruct { val: 3 }));
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 1);
+
+        assert!(locator.insert_with_if_absent(|_| String::from("hello")));
+        assert_eq!(locator.get::<String>().unwrap(), "hello");
+    }
+
+    #[test]
+    fn test_insert_singleton() {
+        struct Service {
+            val: i32,
+        }
+
+        let mut locator = Locator::new();
+
+        assert!(locator.insert_singleton(Service { val: 42 }).is_none());
+        assert!(!locator.contains::<Service>());
+
+        let first = locator.get_arc::<Service>().unwrap();
+        let strong_count = Arc::strong_count(&first);
+
+        let second = locator.get_arc::<Service>().unwrap();
+        assert_eq!(Arc::strong_count(&first), strong_count + 1);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(second.val, 42);
+    }
+
+    #[test]
+    fn test_insert_weak_with() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let created = Arc::new(AtomicUsize::new(0));
+        let mut locator = Locator::new();
+        locator.insert_weak_with({
+            let created = created.clone();
+            move |_| MyStruct {
+                val: created.fetch_add(1, Ordering::SeqCst) as i32,
+            }
+        });
+
+        let first = locator.get_arc::<MyStruct>().unwrap();
+        let second = locator.get_arc::<MyStruct>().unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(created.load(Ordering::SeqCst), 1);
+
+        drop(first);
+        drop(second);
+
+        let third = locator.get_arc::<MyStruct>().unwrap();
+        assert_eq!(third.val, 1);
+        assert_eq!(created.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_insert_singleton_and_insert_do_not_collide() {
+        let mut locator = Locator::new();
+
+        locator.insert(MyStruct { val: 1 });
+        locator.insert_singleton(MyStruct { val: 2 });
+
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 1);
+        assert_eq!(locator.get_arc::<MyStruct>().unwrap().val, 2);
+    }
+
+    #[tokio::test]
+    async fn test_insert_async_with() {
+        let mut locator = Locator::new();
+
+        locator.insert(MyStruct { val: 42 });
+        locator.insert_async_with(|locator| {
+            let my_struct = locator.get::<MyStruct>().unwrap();
+            async move { my_struct.val.to_string() }
+        });
+
+        assert!(locator.get::<String>().is_none());
+        assert_eq!(locator.get_async::<String>().await.unwrap(), "42");
+        assert_eq!(locator.get_async::<MyStruct>().await.unwrap().val, 42);
+    }
+
+    #[tokio::test]
+    async fn test_get_async_timeout() {
+        let mut locator = Locator::new();
+
+        locator.insert_async_with(|_| async {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            String::from("never")
+        });
+        locator.insert_async_with(|_| async { 42_i32 });
+
+        let err = locator
+            .get_async_timeout::<String>(Duration::from_millis(20))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, LocatorError::Timeout { elapsed, .. } if elapsed >= Duration::from_millis(20))
+        );
+
+        let err = locator
+            .get_async_timeout_with::<String, _, _>(Duration::from_millis(20), tokio::time::sleep)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LocatorError::Timeout { .. }));
+
+        let value = locator
+            .get_async_timeout::<i32>(Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert_eq!(value, 42);
+
+        let err = locator
+            .get_async_timeout::<u64>(Duration::from_secs(1))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LocatorError::NotFound { .. }));
+    }
+
+    #[test]
+    fn test_insert_cached_with() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let transient_calls = Arc::new(AtomicUsize::new(0));
+        let cached_calls = Arc::new(AtomicUsize::new(0));
+        let mut locator = Locator::new();
+
+        locator.insert_with({
+            let calls = transient_calls.clone();
+            move |_| MyStruct {
+                val: calls.fetch_add(1, Ordering::SeqCst) as i32,
+            }
+        });
+
+        locator.insert_cached_with({
+            let calls = cached_calls.clone();
+            move |_| calls.fetch_add(1, Ordering::SeqCst).to_string()
+        });
+
+        for _ in 0..3 {
+            locator.get::<MyStruct>().unwrap();
+            assert_eq!(locator.get::<String>().unwrap(), "0");
+        }
+
+        assert_eq!(transient_calls.load(Ordering::SeqCst), 3);
+        assert_eq!(cached_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(locator.get_ref::<String>().unwrap(), "0");
+    }
+
+    #[test]
+    fn test_register_lifetimes() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let mut locator = Locator::new();
+        let counter = Arc::new(AtomicUsize::new(0));
+        let next = move |_: &Locator| counter.fetch_add(1, Ordering::SeqCst);
+
+        locator.register(Lifetime::Transient, {
+            let next = next.clone();
+            move |locator| MyStruct {
+                val: next(locator) as i32,
+            }
+        });
+        locator.register(Lifetime::Singleton, {
+            let next = next.clone();
+            move |locator| next(locator).to_string()
+        });
+        locator.register(Lifetime::Scoped, {
+            let next = next.clone();
+            move |locator| next(locator) as u64
+        });
+
+        let transient = locator.get::<MyStruct>().unwrap().val;
+        assert_ne!(transient, locator.get::<MyStruct>().unwrap().val);
+
+        let singleton = locator.get::<String>().unwrap();
+        assert_eq!(singleton, locator.get::<String>().unwrap());
+
+        let scoped = locator.get::<u64>().unwrap();
+        assert_eq!(scoped, locator.get::<u64>().unwrap());
+
+        let locator = Arc::new(locator);
+        let scope = locator.scope();
+        assert_eq!(scope.get::<String>().unwrap(), singleton);
+
+        let scope_value = scope.get::<u64>().unwrap();
+        assert_ne!(scope_value, scoped);
+        assert_eq!(scope_value, scope.get::<u64>().unwrap());
+        assert_ne!(scope_value, locator.scope().get::<u64>().unwrap());
+    }
+
+    #[test]
+    fn test_register_scoped() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut locator = Locator::new();
+        locator.register_scoped({
+            let calls = calls.clone();
+            move |_| {
+                Arc::new(MyStruct {
+                    val: calls.fetch_add(1, Ordering::SeqCst) as i32,
+                })
+            }
+        });
+
+        let locator = Arc::new(locator);
+        let first = locator.scope();
+        let second = locator.scope();
+
+        let a = first.get::<Arc<MyStruct>>().unwrap();
+        assert!(Arc::ptr_eq(&a, &first.get::<Arc<MyStruct>>().unwrap()));
+
+        let b = second.get::<Arc<MyStruct>>().unwrap();
+        assert!(Arc::ptr_eq(&b, &second.get::<Arc<MyStruct>>().unwrap()));
+        assert!(!Arc::ptr_eq(&a, &b));
+
+        // The parent has no value until it resolves its own.
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(locator.scoped.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_register_factory_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let reads = Arc::new(AtomicUsize::new(0));
+        let mut locator = Locator::new();
+        locator.insert_with({
+            let reads = reads.clone();
+            move |_| {
+                reads.fetch_add(1, Ordering::SeqCst);
+                MyStruct { val: 42 }
+            }
+        });
+        locator.register_factory_once(|locator| {
+            let my_struct = locator.get::<MyStruct>().unwrap();
+            format!("value: {}", my_struct.val)
+        });
+
+        for _ in 0..10 {
+            assert_eq!(locator.get::<String>().unwrap(), "value: 42");
+        }
+
+        assert_eq!(reads.load(Ordering::SeqCst), 1);
+
+        // The dependency is not read again once the value is cached.
+        locator.insert(MyStruct { val: 10 });
+        assert_eq!(locator.get::<String>().unwrap(), "value: 42");
+    }
+
+    #[test]
+    fn test_get_dyn() {
+        let mut locator = Locator::new();
+        locator.insert(MyStruct { val: 42 });
+        locator.insert_with(|_| String::from("hello"));
+        locator.insert_async_with(|_| async { 10_i32 });
+
+        let value = locator.get_dyn(TypeId::of::<MyStruct>()).unwrap();
+        assert_eq!(value.downcast::<MyStruct>().unwrap().val, 42);
+
+        let value = locator.get_dyn(TypeId::of::<String>()).unwrap();
+        assert_eq!(*value.downcast::<String>().unwrap(), "hello");
+
+        assert!(locator.get_dyn(TypeId::of::<i32>()).is_none());
+        assert!(locator.get_dyn(TypeId::of::<bool>()).is_none());
+    }
+
+    #[test]
+    fn test_get_dyn_records_metrics() {
+        use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+        #[derive(Default)]
+        struct Counter(Mutex<HashMap<&'static str, usize>>);
+
+        impl MetricsCollector for Arc<Counter> {
+            fn record(&self, type_name: &'static str, _elapsed: Duration) {
+                *self.0.lock().unwrap().entry(type_name).or_default() += 1;
+            }
+        }
+
+        let counter = Arc::new(Counter::default());
+        let mut locator = Locator::new().with_metrics(counter.clone());
+        locator.insert(MyStruct { val: 42 });
+        locator.insert_with(|locator| locator.get::<MyStruct>().unwrap().val.to_string());
+
+        locator.get_dyn(TypeId::of::<String>()).unwrap();
+        assert!(locator.get_dyn(TypeId::of::<i32>()).is_none());
+
+        let counts = counter.0.lock().unwrap();
+        assert_eq!(counts[type_name::<String>()], 1);
+        assert_eq!(counts[type_name::<MyStruct>()], 1);
+        assert!(!counts.contains_key(type_name::<i32>()));
+    }
+
+    #[test]
+    fn test_resolve() {
+        let mut locator = Locator::new();
+        locator.insert(MyStruct { val: 42 });
+        assert_eq!(locator.resolve::<MyStruct>().unwrap().val, 42);
+
+        let err = locator.resolve::<String>().unwrap_err();
+        assert!(matches!(err, LocatorError::NotFound { .. }));
+
+        locator.unchecked_insert_with_type_name(
+            TypeId::of::<String>(),
+            "i32",
+            Provider::Factory(Arc::new(|_| Box::new(10_i32))),
+        );
+
+        let err = locator.resolve::<String>().unwrap_err();
+        assert!(matches!(
+            err,
+            LocatorError::DowncastMismatch {
+                expected: "alloc::string::String",
+                found: "i32"
+            }
+        ));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(
+        expected = "the provider of `alloc::string::String` returned a value of other type"
+    )]
+    fn test_get_with_mismatched_provider() {
+        let mut locator = Locator::new();
+        locator.unchecked_insert(
+            TypeId::of::<String>(),
+            Provider::Factory(Arc::new(|_| Box::new(10_i32))),
+        );
+
+        locator.get::<String>();
+    }
+
+    #[test]
+    fn test_get_many() {
+        let mut locator = Locator::new();
+        locator.insert(MyStruct { val: 42 });
+        locator.insert(String::from("hello"));
+        locator.insert(10_i32);
+
+        let (my_struct, s, n): (MyStruct, String, i32) = locator.get_many().unwrap();
+        assert_eq!(my_struct.val, 42);
+        assert_eq!(s, "hello");
+        assert_eq!(n, 10);
+
+        let err = locator.get_many::<(MyStruct, u64)>().unwrap_err();
+        assert!(matches!(
+            err,
+            LocatorError::NotFound {
+                expected: "u64",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_get_or_insert_with() {
+        let mut locator = Locator::new();
+        let mut calls = 0;
+
+        let value = locator.get_or_insert_with(|_| {
+            calls += 1;
+            MyStruct { val: 1 }
+        });
+        assert_eq!(value.val, 1);
+
+        let value = locator.get_or_insert_with(|_| {
+            calls += 1;
+            MyStruct { val: 2 }
+        });
+        assert_eq!(value.val, 1);
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_get_or_insert_with_reuses_factory() {
+        let mut locator = Locator::new();
+        locator.insert_with(|_| MyStruct { val: 1 });
+
+        let value = locator.get_or_insert_with(|_| MyStruct { val: 2 });
+        assert_eq!(value.val, 1);
+        assert!(matches!(
+            locator.unchecked_get(&TypeId::of::<MyStruct>()),
+            Some(Provider::Factory(_))
+        ));
+    }
+
+    #[test]
+    fn test_get_ref() {
+        let mut locator = Locator::new();
+
+        locator.insert(MyStruct { val: 42 });
+        locator.insert_with(|_| String::from("factory"));
+
+        assert_eq!(locator.get_ref::<MyStruct>(), Some(&MyStruct { val: 42 }));
+        assert!(locator.get_ref::<String>().is_none());
+        assert!(locator.get_ref::<bool>().is_none());
+    }
+
+    #[test]
+    fn test_insert_named() {
+        let mut locator = Locator::new();
+
+        locator.insert_named("a", String::from("first"));
+        locator.insert_with_named("b", |_| String::from("second"));
+
+        assert_eq!(locator.get_named::<String>("a").unwrap(), "first");
+        assert_eq!(locator.get_named::<String>("b").unwrap(), "second");
+        assert!(locator.get::<String>().is_none());
+        assert!(locator.get_named::<String>("c").is_none());
+
+        assert!(locator.contains_named::<String>("a"));
+        assert!(locator.remove_named::<String>("a").is_some());
+        assert!(!locator.contains_named::<String>("a"));
+        assert!(locator.contains_named::<String>("b"));
+    }
+
+    #[test]
+    fn test_register_many() {
+        trait Handler: Send + Sync {
+            fn name(&self) -> &'static str;
+        }
+
+        struct A;
+        impl Handler for A {
+            fn name(&self) -> &'static str {
+                "a"
+            }
+        }
+
+        struct B;
+        impl Handler for B {
+            fn name(&self) -> &'static str {
+                "b"
+            }
+        }
+
+        let mut locator = Locator::new();
+        assert!(locator.get_all::<Box<dyn Handler>>().is_empty());
+
+        locator.register_many_with::<_, Box<dyn Handler>>(|_| Box::new(A));
+        locator.register_many_with::<_, Box<dyn Handler>>(|_| Box::new(B));
+        locator.register_many_with::<_, Box<dyn Handler>>(|_| Box::new(A));
+
+        let names = locator
+            .get_all::<Box<dyn Handler>>()
+            .iter()
+            .map(|h| h.name())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, vec!["a", "b", "a"]);
+        assert!(!locator.contains::<Box<dyn Handler>>());
+    }
+
+    #[test]
+    fn test_register_many_is_separate_from_insert() {
+        let mut locator = Locator::new();
+
+        locator.insert(MyStruct { val: 1 });
+        locator.register_many(MyStruct { val: 2 });
+        locator.register_many(MyStruct { val: 3 });
+
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 1);
+        assert_eq!(
+            locator.get_all::<MyStruct>(),
+            vec![MyStruct { val: 2 }, MyStruct { val: 3 }]
+        );
+    }
+
+    #[test]
+    fn test_invoke_all() {
+        let mut locator = Locator::new();
+        locator.register_many(MyStruct { val: 1 });
+        locator.register_many(MyStruct { val: 2 });
+        locator.register_many_with(|_| MyStruct { val: 3 });
+
+        let mut seen = Vec::new();
+        let results = locator.invoke_all(|s: MyStruct| {
+            seen.push(s.val);
+            s.val * 10
+        });
+
+        assert_eq!(seen, vec![1, 2, 3]);
+        assert_eq!(results, vec![10, 20, 30]);
+    }
+
+    #[test]
+    fn test_contains() {
+        let mut locator = Locator::new();
+
+        assert!(!locator.contains::<MyStruct>());
+        locator.insert(MyStruct { val: 42 });
+        assert!(locator.contains::<MyStruct>());
+    }
+
+    #[test]
+    fn test_contains_all() {
+        let mut locator = Locator::new();
+        locator.insert(MyStruct { val: 42 });
+
+        assert!(locator.contains_all::<(MyStruct,)>());
+        assert!(!locator.contains_all::<(MyStruct, String)>());
+
+        locator.insert_with(|_| String::from("hello"));
+        assert!(locator.contains_all::<(MyStruct, String)>());
+    }
+
+    #[test]
+    fn test_contains_type_id() {
+        let mut locator = Locator::new();
+        assert!(!locator.contains_type_id(TypeId::of::<MyStruct>()));
+
+        locator.insert(MyStruct { val: 42 });
+        assert!(locator.contains_type_id(TypeId::of::<MyStruct>()));
+        assert!(!locator.contains_type_id(TypeId::of::<String>()));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut locator = Locator::new();
+
+        locator.insert(MyStruct { val: 42 });
+
+        assert!(locator.contains::<MyStruct>());
+        assert!(locator.remove::<MyStruct>().is_some());
+        assert!(!locator.contains::<MyStruct>());
+    }
+
+    #[test]
+    fn test_remove_named() {
+        let mut locator = Locator::new();
+
+        locator.insert(MyStruct { val: 1 });
+        locator.insert_named("a", MyStruct { val: 2 });
+        locator.insert_named("b", MyStruct { val: 3 });
+
+        assert!(locator.remove_named::<MyStruct>("a").is_some());
+        assert!(locator.remove_named::<MyStruct>("a").is_none());
+        assert!(locator.remove_named::<MyStruct>("c").is_none());
+
+        assert!(locator.get_named::<MyStruct>("a").is_none());
+        assert_eq!(locator.get_named::<MyStruct>("b").unwrap().val, 3);
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 1);
+    }
+
+    #[test]
+    fn test_remove_keeps_named() {
+        let mut locator = Locator::new();
+
+        locator.insert(MyStruct { val: 1 });
+        locator.insert_named("a", MyStruct { val: 2 });
+
+        assert!(locator.remove::<MyStruct>().is_some());
+        assert!(!locator.contains::<MyStruct>());
+        assert_eq!(locator.get_named::<MyStruct>("a").unwrap().val, 2);
+    }
+
+    #[test]
+    fn test_take() {
+        let mut locator = Locator::new();
+
+        locator.insert(MyStruct { val: 42 });
+        locator.insert_with(|_| String::from("hello"));
+
+        assert_eq!(locator.take::<MyStruct>(), Some(MyStruct { val: 42 }));
+        assert_eq!(locator.take::<String>().unwrap(), "hello");
+        assert!(!locator.contains::<MyStruct>());
+        assert!(!locator.contains::<String>());
+        assert!(locator.take::<MyStruct>().is_none());
+    }
+
+    #[test]
+    fn test_take_invoke() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct Connection {
+            clones: Arc<AtomicUsize>,
+        }
+
+        impl Clone for Connection {
+            fn clone(&self) -> Self {
+                self.clones.fetch_add(1, Ordering::SeqCst);
+                Connection {
+                    clones: self.clones.clone(),
+                }
+            }
+        }
+
+        let clones = Arc::new(AtomicUsize::new(0));
+        let mut locator = Locator::new();
+        locator.insert(Connection {
+            clones: clones.clone(),
+        });
+
+        let result =
+            locator.take_invoke(|connection: Connection| Arc::ptr_eq(&connection.clones, &clones));
+
+        assert!(result.unwrap());
+        assert_eq!(clones.load(Ordering::SeqCst), 0);
+        assert!(!locator.contains::<Connection>());
+
+        let err = locator.take_invoke(|_: Connection| ()).unwrap_err();
+        assert!(matches!(err, LocatorError::NotFound { .. }));
+    }
+
+    #[test]
+    fn test_take_invoke_keeps_unresolved_service() {
+        let mut locator = Locator::new();
+        locator.insert_with_fallback(|_| None::<String>, |_| None);
+
+        let err = locator.take_invoke(|_: String| ()).unwrap_err();
+        assert!(matches!(err, LocatorError::NotFound { .. }));
+        assert!(locator.contains::<String>());
+    }
+
+    #[test]
+    fn test_len_and_is_empty() {
+        let mut locator = Locator::new();
+
+        assert!(locator.is_empty());
+        locator.insert(MyStruct { val: 42 });
+
+        assert_eq!(locator.len(), 1);
+        assert!(!locator.is_empty());
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut locator = Locator::new();
+        locator.insert(MyStruct { val: 42 });
+        locator.insert_named("a", String::from("hello"));
+        locator.register_many(1_u32);
+
+        locator.clear();
+
+        assert!(locator.is_empty());
+        assert!(!locator.contains::<MyStruct>());
+        assert!(locator.get_all::<u32>().is_empty());
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut locator = Locator::new();
+        locator.insert(MyStruct { val: 42 });
+        locator.insert(String::from("hello"));
+        locator.insert_with(|_| 10_u32);
+
+        locator.retain(|id| *id != TypeId::of::<String>());
+
+        assert_eq!(locator.len(), 2);
+        assert!(locator.contains::<MyStruct>());
+        assert!(locator.contains::<u32>());
+        assert!(!locator.contains::<String>());
+    }
+
+    #[test]
+    fn test_retain_discards_scoped_values() {
+        let mut locator = Locator::new();
+        locator.register_scoped(|_| MyStruct { val: 1 });
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 1);
+
+        locator.retain(|id| *id != TypeId::of::<MyStruct>());
+
+        let mut other = Locator::new();
+        other.register_scoped(|_| MyStruct { val: 2 });
+        locator.extend(other);
+
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 2);
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut locator1 = Locator::new();
+        let mut locator2 = Locator::new();
+
+        locator1.insert(MyStruct { val: 42 });
+        locator2.insert_with::<_, MyStruct>(|_| MyStruct { val: 10 });
+        locator1.extend(locator2);
+
+        assert_eq!(locator1.get::<MyStruct>().unwrap().val, 10);
+    }
+
+    #[test]
+    fn test_extend_with_keep_existing() {
+        let mut locator1 = Locator::new();
+        let mut locator2 = Locator::new();
+
+        locator1.insert(MyStruct { val: 42 });
+        locator2.insert(MyStruct { val: 10 });
+        locator2.insert(String::from("hello"));
+
+        locator1
+            .extend_with(locator2, MergeStrategy::KeepExisting)
+            .unwrap();
+
+        assert_eq!(locator1.get::<MyStruct>().unwrap().val, 42);
+        assert_eq!(locator1.get::<String>().unwrap(), "hello");
+    }
+
+    #[test]
+    fn test_extend_with_overwrite() {
+        let mut locator1 = Locator::new();
+        let mut locator2 = Locator::new();
+
+        locator1.insert(MyStruct { val: 42 });
+        locator2.insert(MyStruct { val: 10 });
+
+        locator1
+            .extend_with(locator2, MergeStrategy::Overwrite)
+            .unwrap();
+
+        assert_eq!(locator1.get::<MyStruct>().unwrap().val, 10);
+    }
+
+    #[test]
+    fn test_extend_with_error() {
+        let mut locator1 = Locator::new();
+        let mut locator2 = Locator::new();
+
+        locator1.insert(MyStruct { val: 42 });
+        locator2.insert(MyStruct { val: 10 });
+        locator2.insert(String::from("hello"));
+
+        let result = locator1.extend_with(locator2, MergeStrategy::Error);
+
+        assert!(matches!(
+            result,
+            Err(LocatorError::AlreadyRegistered { type_name: name }) if name == type_name::<MyStruct>()
+        ));
+
+        // Nothing is added when the merge fails.
+        assert_eq!(locator1.get::<MyStruct>().unwrap().val, 42);
+        assert!(!locator1.contains::<String>());
+    }
+
+    #[test]
+    fn test_extend_with_error_reports_first_conflict_by_name() {
+        fn locator() -> Locator {
+            let mut locator = Locator::new();
+            locator.insert(MyStruct { val: 42 });
+            locator.insert(String::from("hello"));
+            locator.insert(10_i32);
+            locator.insert(20_u64);
+            locator
+        }
+
+        let mut names = [
+            type_name::<MyStruct>(),
+            type_name::<String>(),
+            type_name::<i32>(),
+            type_name::<u64>(),
+        ];
+        names.sort();
+
+        for _ in 0..10 {
+            let result = locator().extend_with(locator(), MergeStrategy::Error);
+            assert!(matches!(
+                result,
+                Err(LocatorError::AlreadyRegistered { type_name }) if type_name == names[0]
+            ));
+        }
+    }
+
+    #[test]
+    fn test_try_extend() {
+        use crate::try_locator::TryLocator;
+
+        fn locators() -> (Locator, Locator) {
+            let mut locator1 = Locator::new();
+            locator1.insert(10_i32);
+            locator1.try_insert_with(|_| Ok(MyStruct { val: 1 }));
+
+            let mut locator2 = Locator::new();
+            locator2.insert(20_i32);
+            locator2.try_insert_with(|_| Ok(MyStruct { val: 2 }));
+            locator2.try_insert_with(|_| Ok(String::from("hello")));
+
+            (locator1, locator2)
+        }
+
+        let conflicts = vec![type_name::<Result<MyStruct, LocatorError>>()];
+
+        let (mut locator1, locator2) = locators();
+        let result = locator1.try_extend(locator2, MergeStrategy::Overwrite);
+        assert_eq!(result.unwrap(), conflicts);
+        assert_eq!(locator1.try_get::<MyStruct>().unwrap().val, 2);
+        assert_eq!(locator1.get::<i32>().unwrap(), 20);
+        assert_eq!(locator1.try_get::<String>().unwrap(), "hello");
+
+        let (mut locator1, locator2) = locators();
+        let result = locator1.try_extend(locator2, MergeStrategy::KeepExisting);
+        assert_eq!(result.unwrap(), conflicts);
+        assert_eq!(locator1.try_get::<MyStruct>().unwrap().val, 1);
+        assert_eq!(locator1.get::<i32>().unwrap(), 20);
+        assert_eq!(locator1.try_get::<String>().unwrap(), "hello");
+
+        let (mut locator1, locator2) = locators();
+        let result = locator1.try_extend(locator2, MergeStrategy::Error);
+        assert!(matches!(
+            result,
+            Err(LocatorError::AlreadyRegistered { type_name }) if type_name == conflicts[0]
+        ));
+        assert_eq!(locator1.try_get::<MyStruct>().unwrap().val, 1);
+        assert_eq!(locator1.get::<i32>().unwrap(), 10);
+        assert!(locator1.try_get::<String>().is_err());
+    }
+
+    #[test]
+    fn test_install() {
+        struct AppModule {
+            val: i32,
+        }
+
+        impl Module for AppModule {
+            fn register(&self, locator: &mut Locator) {
+                let val = self.val;
+                locator.insert(MyStruct { val });
+                locator.insert_with(move |_| format!("value: {val}"));
+            }
+        }
+
+        let mut locator = Locator::new();
+        locator.install(AppModule { val: 42 }).insert(1_u8);
+
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 42);
+        assert_eq!(locator.get::<String>().unwrap(), "value: 42");
+        assert_eq!(locator.len(), 3);
+    }
+
+    #[test]
+    fn test_scope() {
+        let mut parent = Locator::new();
+        parent.insert(MyStruct { val: 1 });
+        parent.insert(String::from("parent"));
+
+        let parent = Arc::new(parent);
+        let mut child = parent.scope();
+        child.insert(MyStruct { val: 2 });
+
+        assert_eq!(parent.get::<MyStruct>().unwrap().val, 1);
+        assert_eq!(child.get::<MyStruct>().unwrap().val, 2);
+
+        assert_eq!(child.get::<String>().unwrap(), "parent");
+        assert!(child.contains::<String>());
+        assert_eq!(child.len(), 1);
+    }
+
+    #[test]
+    fn test_with_override() {
+        let mut root = Locator::new();
+        root.insert(MyStruct { val: 1 });
+        root.insert_with(|locator| locator.get::<MyStruct>().unwrap().val.to_string());
+
+        let root = Arc::new(root);
+        let result = root.with_override(MyStruct { val: 2 }, |scoped| {
+            assert_eq!(scoped.get::<MyStruct>().unwrap().val, 2);
+            scoped.get::<String>().unwrap()
+        });
+
+        assert_eq!(result, "2");
+        assert_eq!(root.get::<MyStruct>().unwrap().val, 1);
+        assert_eq!(root.get::<String>().unwrap(), "1");
+    }
+
+    #[test]
+    fn test_scope_factory_resolves_from_child() {
+        let mut parent = Locator::new();
+        parent.insert(MyStruct { val: 1 });
+        parent.insert_with(|locator| locator.get::<MyStruct>().unwrap().val.to_string());
+
+        let parent = Arc::new(parent);
+        let mut child = parent.scope();
+        child.insert(MyStruct { val: 2 });
+
+        assert_eq!(parent.get::<String>().unwrap(), "1");
+        assert_eq!(child.get::<String>().unwrap(), "2");
+        assert!(!parent.contains::<bool>());
+    }
+
+    #[test]
+    fn test_clone() {
+        let mut locator = Locator::new();
+        locator.insert(MyStruct { val: 1 });
+        locator.insert_with(|locator| locator.get::<MyStruct>().unwrap().val.to_string());
+
+        let mut cloned = locator.clone();
+        cloned.insert(MyStruct { val: 2 });
+        cloned.insert(true);
+
+        assert_eq!(cloned.get::<String>().unwrap(), "2");
+        assert_eq!(locator.get::<String>().unwrap(), "1");
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 1);
+        assert!(!locator.contains::<bool>());
+        assert_eq!(locator.len(), 2);
+    }
+
+    #[test]
+    fn test_not_found_suggests_similar_types() {
+        trait UserRepository {}
+        struct InMemoryRepository;
+        impl UserRepository for InMemoryRepository {}
+
+        let mut locator = Locator::new();
+        locator.insert(String::from("hello"));
+        locator.insert_with(|_| -> Box<dyn UserRepository + Send + Sync> {
+            Box::new(InMemoryRepository)
+        });
+
+        let err = locator.not_found::<Box<dyn UserRepository>>();
+        match &err {
+            LocatorError::NotFound { suggestions, .. } => assert_eq!(
+                suggestions,
+                &[type_name::<Box<dyn UserRepository + Send + Sync>>()]
+            ),
+            _ => panic!("expected a not found error"),
+        }
+
+        assert!(err.to_string().contains("did you mean"));
+    }
+
+    #[test]
+    fn test_invoke_scoped() {
+        #[derive(Default)]
+        struct Counter {
+            count: i32,
+        }
+
+        let mut locator = Locator::new();
+        locator.insert_mut(Counter::default());
+
+        for _ in 0..3 {
+            locator
+                .invoke_scoped(|counter: &mut Counter| counter.count += 1)
+                .unwrap();
+        }
+
+        locator
+            .invoke(|counter: Mut<Counter>| counter.write().count += 1)
+            .unwrap();
+
+        let count = locator.invoke_scoped(|counter: &mut Counter| counter.count);
+        assert_eq!(count.unwrap(), 4);
+        assert!(locator.invoke_scoped(|_: &mut MyStruct| ()).is_err());
+    }
+
+    #[test]
+    fn test_replace_with() {
+        let mut locator = Locator::new();
+        assert!(locator.replace_with(|_| MyStruct { val: 0 }).is_none());
+
+        locator.insert_cached_with(|_| MyStruct { val: 1 });
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 1);
+
+        let prev = locator.replace_with(|_| MyStruct { val: 2 }).unwrap();
+        assert!(matches!(prev, Provider::Cached(_)));
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 2);
+        assert!(matches!(
+            locator.unchecked_get(&TypeId::of::<MyStruct>()),
+            Some(Provider::Cached(_))
+        ));
+    }
+
+    #[test]
+    fn test_replace_with_keeps_singleton() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut locator = Locator::new();
+        locator.insert(MyStruct { val: 1 });
+
+        let prev = locator.replace_with({
+            let calls = calls.clone();
+            move |_| MyStruct {
+                val: calls.fetch_add(1, Ordering::SeqCst) as i32 + 2,
+            }
+        });
+
+        assert!(matches!(prev, Some(Provider::Single(_))));
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 2);
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 2);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_replace_with_keeps_scoped() {
+        let mut locator = Locator::new();
+        locator.register_scoped(|_| Arc::new(MyStruct { val: 1 }));
+        assert_eq!(locator.get::<Arc<MyStruct>>().unwrap().val, 1);
+
+        locator.replace_with(|_| Arc::new(MyStruct { val: 2 }));
+
+        let locator = Arc::new(locator);
+        let scope = locator.scope();
+        let a = scope.get::<Arc<MyStruct>>().unwrap();
+        assert_eq!(a.val, 2);
+        assert!(Arc::ptr_eq(&a, &scope.get::<Arc<MyStruct>>().unwrap()));
+
+        let b = locator.get::<Arc<MyStruct>>().unwrap();
+        assert_eq!(b.val, 2);
+        assert!(Arc::ptr_eq(&b, &locator.get::<Arc<MyStruct>>().unwrap()));
+        assert!(!Arc::ptr_eq(&a, &b));
+    }
+
+    #[test]
+    fn test_insert_lazy() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut locator = Locator::new();
+        locator.insert_lazy({
+            let calls = calls.clone();
+            move || {
+                calls.fetch_add(1, Ordering::SeqCst);
+                MyStruct { val: 42 }
+            }
+        });
+
+        // Resolving the service doesn't create the value.
+        let lazy = locator.get::<Lazy<MyStruct>>().unwrap();
+        locator.invoke(|_: Lazy<MyStruct>| ()).unwrap();
+        assert!(!lazy.is_initialized());
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        let val = locator
+            .invoke(|config: Lazy<MyStruct>| config.get().val)
+            .unwrap();
+
+        assert_eq!(val, 42);
+        assert_eq!(lazy.get().val, 42);
+        assert!(lazy.is_initialized());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_replace() {
+        let mut locator = Locator::new();
+        assert!(locator.replace(MyStruct { val: 1 }).is_none());
+
+        let old = locator.replace(MyStruct { val: 2 }).unwrap();
+        assert_eq!(old.val, 1);
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 2);
+
+        locator.insert_with(|_| String::from("factory"));
+        assert_eq!(locator.replace(String::from("value")).unwrap(), "factory");
+        assert_eq!(locator.get::<String>().unwrap(), "value");
+    }
+
+    #[tokio::test]
+    async fn test_initialize_all_async() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        let mut locator = Locator::new();
+        locator.insert_async_singleton_with(|_| async {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            MyStruct { val: 42 }
+        });
+        locator.insert_async_singleton_with(|_| async {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            String::from("hello")
+        });
+
+        locator.initialize_all_async().await.unwrap();
+        assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+
+        assert_eq!(locator.get_async::<MyStruct>().await.unwrap().val, 42);
+        assert_eq!(locator.get_async::<String>().await.unwrap(), "hello");
+        assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_with() {
+        let locator = Arc::new(
+            Locator::new()
+                .with(MyStruct { val: 42 })
+                .with(String::from("hello"))
+                .with_factory(|locator| locator.get::<MyStruct>().unwrap().val as usize),
+        );
+
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 42);
+        assert_eq!(locator.get::<String>().unwrap(), "hello");
+        assert_eq!(locator.get::<usize>(), Some(42));
+    }
+
+    #[test]
+    fn test_with_metrics() {
+        use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+        #[derive(Default)]
+        struct Counter(Mutex<HashMap<&'static str, usize>>);
+
+        impl MetricsCollector for Arc<Counter> {
+            fn record(&self, type_name: &'static str, _elapsed: Duration) {
+                *self.0.lock().unwrap().entry(type_name).or_default() += 1;
+            }
+        }
+
+        let counter = Arc::new(Counter::default());
+        let mut locator = Locator::new().with_metrics(counter.clone());
+        locator.insert(MyStruct { val: 42 });
+        locator.insert_with(|locator| locator.get::<MyStruct>().unwrap().val.to_string());
+
+        for _ in 0..3 {
+            locator.get::<MyStruct>().unwrap();
+        }
+        locator.get::<String>().unwrap();
+        locator.get::<i32>();
+
+        let counts = counter.0.lock().unwrap();
+        assert_eq!(counts[type_name::<MyStruct>()], 4);
+        assert_eq!(counts[type_name::<String>()], 1);
+        assert!(!counts.contains_key(type_name::<i32>()));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        #[derive(Clone)]
+        struct A;
+
+        #[derive(Clone)]
+        struct B;
+
+        let mut locator = Locator::new();
+        locator.insert(B);
+        locator.insert_with(|locator| {
+            locator.get::<B>().unwrap();
+            A
+        });
+
+        let dot = locator.to_dot();
+        let a = type_name::<A>();
+        let b = type_name::<B>();
+
+        assert!(dot.starts_with("digraph {"));
+        assert!(dot.contains(&format!("\"{a}\";")));
+        assert!(dot.contains(&format!("\"{b}\";")));
+        assert!(dot.contains(&format!("\"{a}\" -> \"{b}\";")));
+        assert!(!dot.contains(&format!("\"{b}\" -> ")));
+    }
+
+    #[test]
+    fn test_to_dot_cyclic() {
+        #[derive(Clone)]
+        struct A;
+
+        #[derive(Clone)]
+        struct B;
+
+        let mut locator = Locator::new();
+        locator.insert_with(|locator| {
+            locator.get::<B>().unwrap();
+            A
+        });
+        locator.insert_with(|locator| {
+            locator.get::<A>().unwrap();
+            B
+        });
+
+        let dot = locator.to_dot();
+        let a = type_name::<A>();
+        let b = type_name::<B>();
+
+        assert!(dot.contains(&format!("\"{a}\" -> \"{b}\";")));
+        assert!(dot.contains(&format!("\"{b}\" -> \"{a}\";")));
+    }
+
+    #[test]
+    fn test_entry_or_insert() {
+        let mut locator = Locator::new();
+
+        locator.entry::<MyStruct>().or_insert(MyStruct { val: 1 });
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 1);
+
+        locator.entry::<MyStruct>().or_insert(MyStruct { val: 2 });
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 1);
+
+        match locator.entry::<MyStruct>() {
+            ServiceEntry::Occupied(mut entry) => {
+                assert!(matches!(entry.provider(), Provider::Single(_)));
+                entry.insert(MyStruct { val: 3 });
+            }
+            ServiceEntry::Vacant(_) => panic!("expected an occupied entry"),
+        }
+
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 3);
+    }
+
+    #[test]
+    fn test_entry_or_insert_with() {
+        let mut locator = Locator::new();
+
+        locator
+            .entry::<String>()
+            .or_insert_with(|| String::from("default"));
+        assert_eq!(locator.get::<String>().unwrap(), "default");
+
+        locator
+            .entry::<String>()
+            .or_insert_with(|| panic!("the service is already registered"));
+        assert_eq!(locator.get::<String>().unwrap(), "default");
+
+        match locator.entry::<i32>() {
+            ServiceEntry::Vacant(entry) => {
+                entry.insert_with(|_| 42);
+            }
+            ServiceEntry::Occupied(_) => panic!("expected a vacant entry"),
+        }
+
+        assert_eq!(locator.get::<i32>().unwrap(), 42);
+        assert!(locator.verify().is_ok());
+    }
+
+    #[test]
+    fn test_get_or_default() {
+        let mut locator = Locator::new();
+        locator.insert(String::from("hello"));
+
+        assert_eq!(locator.get_or_default::<String>(), "hello");
+        assert_eq!(locator.get_or_default::<i32>(), 0);
+        assert!(!locator.contains::<i32>());
+    }
+
+    #[test]
+    fn test_get_or() {
+        let mut locator = Locator::new();
+        locator.insert(String::from("hello"));
+
+        assert_eq!(locator.get_or(String::from("world")), "hello");
+        assert_eq!(locator.get_or(42_i32), 42);
+        assert!(!locator.contains::<i32>());
+    }
+
+    #[test]
+    fn test_get_or_else() {
+        let mut locator = Locator::new();
+        locator.insert(String::from("hello"));
+
+        assert_eq!(
+            locator.get_or_else(|| -> String { panic!("the value exists") }),
+            "hello"
+        );
+        assert_eq!(locator.get_or_else(|| 42_i32), 42);
+        assert!(!locator.contains::<i32>());
+    }
+
+    #[test]
+    fn test_insert_when() {
+        let mut locator = Locator::new();
+
+        assert!(!locator.insert_when(false, MyStruct { val: 1 }));
+        assert!(!locator.insert_with_when(false, |_| String::from("hello")));
+        assert!(locator.is_empty());
+
+        assert!(locator.insert_when(true, MyStruct { val: 2 }));
+        assert!(locator.insert_with_when(true, |_| String::from("world")));
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 2);
+        assert_eq!(locator.get::<String>().unwrap(), "world");
+    }
+
+    #[test]
+    fn test_insert_with_fallback() {
+        let mut locator = Locator::new();
+        locator.insert_with_fallback(
+            |locator| locator.get::<MyStruct>().map(|s| s.val.to_string()),
+            |_| Some(String::from("fallback")),
+        );
+
+        assert_eq!(locator.get::<String>().unwrap(), "fallback");
+
+        locator.insert(MyStruct { val: 42 });
+        assert_eq!(locator.get::<String>().unwrap(), "42");
+
+        locator.insert_with_fallback::<_, _, i32>(|_| None, |_| None);
+        assert!(locator.get::<i32>().is_none());
+    }
+
+    #[test]
+    fn test_decorate() {
+        let mut locator = Locator::new();
+        assert!(!locator.decorate(|s: String, _| s));
+
+        locator.insert(String::from("hello"));
+        assert!(locator.decorate(|s: String, _| s + " world"));
+        assert!(locator.decorate(|s: String, _| s + "!"));
+
+        assert_eq!(locator.get::<String>().unwrap(), "hello world!");
+    }
+
+    #[test]
+    fn test_alias() {
+        trait Greeter: Send + Sync {
+            fn greet(&self) -> String;
+        }
+
+        impl Greeter for MyStruct {
+            fn greet(&self) -> String {
+                format!("hello {}", self.val)
+            }
+        }
+
+        let mut locator = Locator::new();
+        locator.alias(|x: MyStruct| Box::new(x) as Box<dyn Greeter>);
+        assert!(locator.get::<Box<dyn Greeter>>().is_none());
+
+        locator.insert(MyStruct { val: 42 });
+        let greeter = locator.get::<Box<dyn Greeter>>().unwrap();
+        assert_eq!(greeter.greet(), "hello 42");
+    }
+
+    #[test]
+    fn test_map_service() {
+        #[derive(Debug, Clone)]
+        struct Name(String);
+
+        let mut locator = Locator::new();
+        assert!(!locator.map_service(Name));
+
+        locator.insert(String::from("kizuna"));
+        assert!(locator.map_service(Name));
+
+        assert!(!locator.contains::<String>());
+        assert_eq!(locator.get::<Name>().unwrap().0, "kizuna");
+    }
+
+    #[test]
+    fn test_map_service_keeps_unresolved_service() {
+        let mut locator = Locator::new();
+        locator.insert_with_fallback(|_| None::<String>, |_| None);
+
+        assert!(!locator.map_service(|s: String| s.len()));
+        assert!(locator.contains::<String>());
+        assert!(!locator.contains::<usize>());
+    }
+
+    #[test]
+    fn test_map_service_ignores_parent_services() {
+        let mut parent = Locator::new();
+        parent.insert(String::from("kizuna"));
+
+        let mut scope = Arc::new(parent).scope();
+        assert!(!scope.map_service(|s: String| s.len()));
+        assert!(!scope.contains::<usize>());
+        assert_eq!(scope.get::<String>().unwrap(), "kizuna");
+    }
+
+    #[test]
+    fn test_shutdown() {
+        use std::sync::Mutex;
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut locator = Locator::new();
+        locator.insert(MyStruct { val: 1 });
+
+        locator.on_shutdown({
+            let order = order.clone();
+            move |locator| {
+                let val = locator.get::<MyStruct>().unwrap().val;
+                order.lock().unwrap().push(("first", val));
+            }
+        });
+
+        locator.on_shutdown({
+            let order = order.clone();
+            move |_| order.lock().unwrap().push(("second", 0))
+        });
+
+        assert!(order.lock().unwrap().is_empty());
+        locator.shutdown();
+        assert_eq!(*order.lock().unwrap(), vec![("second", 0), ("first", 1)]);
+    }
+
+    #[test]
+    fn test_shutdown_after_extend() {
+        use std::sync::Mutex;
+
+        fn with_hook(order: &Arc<Mutex<Vec<&'static str>>>, name: &'static str) -> Locator {
+            let order = order.clone();
+            let mut locator = Locator::new();
+            locator.on_shutdown(move |_| order.lock().unwrap().push(name));
+            locator
+        }
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut locator = with_hook(&order, "first");
+        locator.extend(with_hook(&order, "second"));
+        locator
+            .try_extend(with_hook(&order, "third"), MergeStrategy::Error)
+            .unwrap();
+
+        locator.shutdown();
+        assert_eq!(*order.lock().unwrap(), vec!["third", "second", "first"]);
+    }
+
+    #[test]
+    fn test_clone_into() {
+        let mut source = Locator::new();
+        source.insert(MyStruct { val: 1 });
+        source.insert(String::from("hello"));
 
-/// A wrapper that stores the services from a locator.
-pub enum Provider {
-    Single(Box<dyn Fn() -> Box<dyn Any + Send + Sync> + Send + Sync>),
-    Factory(Box<dyn Fn(&Locator) -> Box<dyn Any + Send + Sync> + Send + Sync>),
-}
+        let mut dest = Locator::new();
+        assert!(source.clone_into::<MyStruct>(&mut dest));
+        assert!(!source.clone_into::<i32>(&mut dest));
 
-/// A service locator.
-#[derive(Default)]
-pub struct Locator(HashMap<TypeId, Provider>);
+        assert_eq!(dest.get::<MyStruct>().unwrap().val, 1);
+        assert!(!dest.contains::<String>());
 
+        dest.insert(MyStruct { val: 2 });
+        assert_eq!(source.get::<MyStruct>().unwrap().val, 1);
 
-impl Locator {
-    /// Inserts a provider without checking the types.
-    #[inline]
-    pub fn unchecked_insert(&mut self, id: TypeId, provider: Provider) -> Option<Provider> {
-        self.0.insert(id, provider)
+        source.remove::<MyStruct>();
+        assert_eq!(dest.get::<MyStruct>().unwrap().val, 2);
     }
 
-    /// Gets a provider for the given type without checking if the types matches.
-    #[inline]
-    pub fn unchecked_get(&self, id: &TypeId) -> Option<&Provider> {
-        self.0.get(id)
-    }
-}
+    #[test]
+    fn test_checkpoint_and_restore() {
+        let mut locator = Locator::new();
+        locator.insert(MyStruct { val: 1 });
 
-impl Locator {
-    /// Creates a new `Locator`.
-    pub fn new() -> Self {
-        Default::default()
-    }
+        let checkpoint = locator.checkpoint();
+        locator.insert(MyStruct { val: 2 });
+        locator.insert(String::from("hello"));
+        locator.register_many(1_i32);
 
-    /// Inserts a value of type `T` into the `Locator`.
-    pub fn insert<T>(&mut self, value: T) -> Option<Provider>
-    where
-        T: Send + Sync + Clone + 'static,
-    {
-        let provider = Provider::Single(Box::new(move || Box::new(value.clone())));
-        self.unchecked_insert(TypeId::of::<T>(), provider)
+        locator.restore(checkpoint);
+
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 1);
+        assert!(!locator.contains::<String>());
+        assert!(locator.get_all::<i32>().is_empty());
+        assert_eq!(locator.len(), 1);
     }
 
-    /// Inserts a value of type `T` into the `Locator` using a factory function that takes a `Locator` as input.
-    pub fn insert_with<F, T>(&mut self, factory: F) -> Option<Provider>
-    where
-        F: Fn(&Self) -> T + 'static + Send + Sync,
-        T: Send + Sync + 'static,
-    {
-        let provider = Provider::Factory(Box::new(move |locator| {
-            let value = factory(locator);
-            Box::new(value)
-        }));
+    #[test]
+    fn test_restore_scoped_values() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
 
-        self.unchecked_insert(TypeId::of::<T>(), provider)
+        let count = Arc::new(AtomicUsize::new(0));
+        let mut locator = Locator::new();
+        locator.register_scoped({
+            let count = count.clone();
+            move |_| Arc::new(MyStruct { val: count.fetch_add(1, Ordering::SeqCst) as i32 })
+        });
+        locator.register_scoped(|_| String::from("hello"));
+
+        let value = locator.get::<Arc<MyStruct>>().unwrap();
+        let checkpoint = locator.checkpoint();
+
+        locator.register_scoped(|_| Arc::new(MyStruct { val: 10 }));
+        assert_eq!(locator.get::<Arc<MyStruct>>().unwrap().val, 10);
+        assert_eq!(locator.get::<String>().unwrap(), "hello");
+
+        locator.restore(checkpoint);
+
+        // The value created before the checkpoint is restored, the one created after is discarded.
+        assert!(Arc::ptr_eq(&value, &locator.get::<Arc<MyStruct>>().unwrap()));
+        assert!(!locator.scoped.read().unwrap().contains_key(&TypeId::of::<String>()));
+        assert_eq!(count.load(Ordering::SeqCst), 1);
     }
 
-    /// Returns a value of type `T` from the `Locator` if it exists.
-    pub fn get<T>(&self) -> Option<T>
-    where
-        T: Send + Sync + 'static,
-    {
-        let provider = self.unchecked_get(&TypeId::of::<T>())?;
+    #[test]
+    fn test_register_impl() {
+        trait Plugin: Send + Sync {
+            fn name(&self) -> String;
+        }
 
-        match provider {
-            Provider::Single(f) => {
-                let value = f();
-                value.downcast::<T>().map(|x| *x).ok()
+        struct Auth;
+        impl Plugin for Auth {
+            fn name(&self) -> String {
+                String::from("auth")
             }
-            Provider::Factory(f) => {
-                let value = f(self);
-                value.downcast::<T>().map(|x| *x).ok()
+        }
+
+        #[derive(Clone)]
+        struct Logger;
+        impl Plugin for Logger {
+            fn name(&self) -> String {
+                String::from("logger")
             }
         }
-    }
 
-    /// Returns a boolean indicating whether a value of type `T` exists in the `Locator`.
-    pub fn contains<T>(&self) -> bool
-    where
-        T: Send + Sync + 'static,
-    {
-        self.0.contains_key(&TypeId::of::<T>())
-    }
+        let mut locator = Locator::new();
+        locator.register_impl::<dyn Plugin, _>(|_| Box::new(Auth));
+        crate::register_impl!(locator, dyn Plugin, Logger);
 
-    /// Removes a value of type `T` from the `Locator` if it exists.
-    pub fn remove<T>(&mut self) -> Option<Provider>
-    where
-        T: Send + Sync + 'static,
-    {
-        self.0.remove(&TypeId::of::<T>())
+        let names = locator
+            .resolve_impls::<dyn Plugin>()
+            .iter()
+            .map(|plugin| plugin.name())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, ["auth", "logger"]);
+        assert!(locator.get::<Box<dyn Plugin>>().is_none());
     }
 
-    /// Returns the number of services in the locator.
-    pub fn len(&self) -> usize {
-        self.0.len()
+    #[test]
+    fn test_len_and_type_count() {
+        let mut locator = Locator::new();
+        assert!(locator.is_empty());
+
+        locator.insert(MyStruct { val: 1 });
+        locator.insert_named("a", MyStruct { val: 2 });
+        locator.insert_named("b", MyStruct { val: 3 });
+        locator.register_many(String::from("x"));
+        locator.register_many(String::from("y"));
+        locator.register_many(MyStruct { val: 4 });
+
+        assert!(!locator.is_empty());
+        assert_eq!(locator.len(), 6);
+        assert_eq!(locator.type_count(), 2);
     }
 
-    /// Returns `true` if the locator is empty.
-    pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+    #[test]
+    fn test_from_iter_and_extend() {
+        let mut source = Locator::new();
+        source.insert(MyStruct { val: 1 });
+        source.insert(String::from("hello"));
+
+        let providers = source
+            .keys()
+            .map(|id| (id, source.unchecked_get(&id).unwrap().clone()))
+            .collect::<Vec<_>>();
+
+        let mut locator = providers.into_iter().collect::<Locator>();
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 1);
+        assert_eq!(locator.get::<String>().unwrap(), "hello");
+
+        let mut other = Locator::new();
+        other.insert(MyStruct { val: 2 });
+        other.insert(10_i32);
+
+        Extend::extend(
+            &mut locator,
+            other
+                .keys()
+                .map(|id| (id, other.unchecked_get(&id).unwrap().clone())),
+        );
+
+        assert_eq!(locator.len(), 3);
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 2);
+        assert_eq!(locator.get::<i32>().unwrap(), 10);
     }
 
-    /// Adds the providers from other locator.
-    pub fn extend(&mut self, other: Self) {
-        self.0.extend(other.0);
+    #[test]
+    fn test_into_iter() {
+        let mut source = Locator::new();
+        source.insert(MyStruct { val: 1 });
+        source.insert_with(|_| String::from("hello"));
+        source.insert_named("a", MyStruct { val: 2 });
+
+        let providers = source.into_iter().collect::<Vec<_>>();
+        assert_eq!(providers.len(), 2);
+
+        let locator = providers
+            .into_iter()
+            .filter(|(id, _)| *id != TypeId::of::<String>())
+            .collect::<Locator>();
+
+        assert_eq!(locator.len(), 1);
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 1);
+        assert!(locator.get::<String>().is_none());
     }
 
-    /// Invoke the given function injecting the dependencies from this locator.
-    pub fn invoke<F, Args>(&self, f: F) -> Result<F::Output, LocatorError>
-    where
-        F: Invoke<Args>,
-        Args: FromLocator,
-    {
-        let args = Args::from_locator(self)?;
-        Ok(Invoke::call(f, args))
+    #[test]
+    fn test_info() {
+        let mut locator = Locator::new();
+        locator.insert(MyStruct { val: 1 });
+        locator.insert_named("a", MyStruct { val: 2 });
+        locator.insert_with(|_| String::from("hello"));
+
+        let info = locator.info();
+        assert_eq!(
+            info.services,
+            vec![
+                ServiceInfo {
+                    type_name: "alloc::string::String",
+                    name: None,
+                    kind: ProviderKind::Factory,
+                },
+                ServiceInfo {
+                    type_name: type_name::<MyStruct>(),
+                    name: None,
+                    kind: ProviderKind::Single,
+                },
+                ServiceInfo {
+                    type_name: type_name::<MyStruct>(),
+                    name: Some("a"),
+                    kind: ProviderKind::Single,
+                },
+            ]
+        );
+
+        assert_eq!(locator.registered().len(), 3);
     }
 
-    /// Invoke the given async function injecting the dependencies from this locator.
-    pub async fn invoke_async<F, Fut, Args>(&self, f: F) -> Result<Fut::Output, LocatorError>
-    where
-        F: AsyncInvoke<Args, Fut = Fut>,
-        Fut: Future,
-        Args: FromLocator,
-    {
-        let args = Args::from_locator(self)?;
-        Ok(AsyncInvoke::call(f, args).await)
+    #[test]
+    fn test_keys() {
+        let mut locator = Locator::new();
+        locator.insert(MyStruct { val: 42 });
+        locator.insert_with(|_| String::from("hello"));
+        locator.insert_named("port", 8080_u16);
+
+        let keys = locator.keys().collect::<std::collections::HashSet<_>>();
+        let expected = [TypeId::of::<MyStruct>(), TypeId::of::<String>()];
+
+        assert_eq!(keys, expected.into_iter().collect());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_verify() {
+        use crate::try_locator::TryLocator;
 
-    #[derive(Clone, Debug, PartialEq)]
-    struct MyStruct {
-        val: i32,
+        let mut locator = Locator::new();
+        assert!(locator.verify().is_ok());
+
+        locator.insert(MyStruct { val: 42 });
+        locator.try_insert_with::<_, MyStruct>(|_| Ok(MyStruct { val: 42 }));
+        locator.try_insert_with::<_, String>(|_| Err(LocatorError::not_found::<String>()));
+
+        let errors = locator.verify().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            LocatorError::NotFound { expected, .. } if *expected == type_name::<String>()
+        ));
     }
 
     #[test]
-    fn test_insert_single() {
+    fn test_verify_cyclic() {
+        #[derive(Clone)]
+        struct A;
+
+        #[derive(Clone)]
+        struct B;
+
         let mut locator = Locator::new();
+        locator.insert_with(|locator| {
+            locator.get::<B>().unwrap();
+            A
+        });
+        locator.insert_cached_with(|locator| {
+            locator.get::<A>().unwrap();
+            B
+        });
 
-        assert!(locator.insert(MyStruct { val: 42 }).is_none());
-        assert_eq!(locator.get::<MyStruct>().unwrap().val, 42);
+        let errors = locator.verify().unwrap_err();
+        assert_eq!(errors.len(), 2);
+
+        for err in errors {
+            let LocatorError::Cyclic { chain } = err else {
+                panic!("expected a cyclic error, got: {err}");
+            };
+
+            assert_eq!(chain.len(), 3);
+            assert_eq!(chain.first(), chain.last());
+        }
+
+        assert!(locator.get::<A>().is_none());
+        assert!(matches!(
+            locator.resolve::<B>(),
+            Err(LocatorError::Cyclic { .. })
+        ));
     }
 
     #[test]
-    fn test_insert_with_factory() {
+    fn test_verify_cyclic_through_fallible() {
+        use crate::try_locator::TryLocator;
+
+        #[derive(Clone, Debug)]
+        struct A;
+
+        #[derive(Clone)]
+        struct B;
+
         let mut locator = Locator::new();
+        locator.try_insert_with::<_, A>(|locator| {
+            locator.get::<B>().unwrap();
+            Ok(A)
+        });
+        locator.insert_with(|locator| {
+            locator.try_get::<A>().unwrap();
+            B
+        });
 
-        locator.insert_with::<_, MyStruct>(|_| MyStruct { val: 42 });
-        assert_eq!(locator.get::<MyStruct>().unwrap().val, 42);
+        let errors = locator.verify().unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(errors
+            .iter()
+            .all(|err| matches!(err, LocatorError::Cyclic { .. })));
+
+        let err = locator.try_get::<A>().unwrap_err();
+        let LocatorError::Cyclic { chain } = err else {
+            panic!("expected a cyclic error, got: {err}");
+        };
+
+        assert_eq!(
+            chain,
+            vec![type_name::<A>(), type_name::<B>(), type_name::<A>()]
+        );
     }
 
     #[test]
-    fn test_contains() {
+    fn test_type_names() {
         let mut locator = Locator::new();
+        locator.insert(MyStruct { val: 42 });
+        locator.insert(10_u32);
+        locator.insert_with(|_| String::from("hello"));
 
-        assert!(!locator.contains::<MyStruct>());
+        let mut names = locator.type_names().collect::<Vec<_>>();
+        names.sort();
+
+        assert_eq!(
+            names,
+            vec![
+                "alloc::string::String",
+                "kizuna::locator::tests::MyStruct",
+                "u32"
+            ]
+        );
+
+        locator.remove::<u32>();
+        assert!(!locator.type_names().any(|name| name == "u32"));
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut locator = Locator::new();
         locator.insert(MyStruct { val: 42 });
-        assert!(locator.contains::<MyStruct>());
+        locator.insert_with(|_| String::from("hello"));
+        locator.insert_cached_with(|_| 10_u32);
+        locator.insert_async_with(|_| async { 20_i64 });
+
+        let mut kinds = locator.iter().collect::<Vec<_>>();
+        kinds.sort();
+
+        assert_eq!(
+            kinds,
+            vec![
+                ("alloc::string::String", ProviderKind::Factory),
+                ("i64", ProviderKind::AsyncFactory),
+                ("kizuna::locator::tests::MyStruct", ProviderKind::Single),
+                ("u32", ProviderKind::Cached),
+            ]
+        );
     }
 
     #[test]
-    fn test_remove() {
+    fn test_provider_kind() {
         let mut locator = Locator::new();
+        assert_eq!(locator.provider_kind::<MyStruct>(), None);
 
         locator.insert(MyStruct { val: 42 });
+        assert_eq!(
+            locator.provider_kind::<MyStruct>(),
+            Some(ProviderKind::Single)
+        );
 
-        assert!(locator.contains::<MyStruct>());
-        assert!(locator.remove::<MyStruct>().is_some());
-        assert!(!locator.contains::<MyStruct>());
+        locator.insert_with(|_| MyStruct { val: 42 });
+        assert_eq!(
+            locator.provider_kind::<MyStruct>(),
+            Some(ProviderKind::Factory)
+        );
     }
 
     #[test]
-    fn test_len_and_is_empty() {
+    fn test_debug() {
         let mut locator = Locator::new();
-
-        assert!(locator.is_empty());
         locator.insert(MyStruct { val: 42 });
+        locator.insert_named("a", String::from("hello"));
+        locator.unchecked_insert(
+            TypeId::of::<bool>(),
+            Provider::Single(Singleton::new(|| true)),
+        );
 
-        assert_eq!(locator.len(), 1);
-        assert!(!locator.is_empty());
+        let debug = format!("{locator:?}");
+        assert!(debug.starts_with("Locator { services: ["));
+        assert!(debug.contains("MyStruct"));
+        assert!(debug.contains(r#"alloc::string::String (\"a\")"#));
+        assert!(debug.contains("<unknown>"));
     }
 
+    #[cfg(feature = "fxhash")]
     #[test]
-    fn test_extend() {
-        let mut locator1 = Locator::new();
-        let mut locator2 = Locator::new();
+    fn test_fast_locator() {
+        let mut locator = FastLocator::default();
 
-        locator1.insert(MyStruct { val: 42 });
-        locator2.insert_with::<_, MyStruct>(|_| MyStruct { val: 10 });
-        locator1.extend(locator2);
+        locator.insert(MyStruct { val: 42 });
+        locator.insert_with(|locator| locator.get::<MyStruct>().unwrap().val.to_string());
 
-        assert_eq!(locator1.get::<MyStruct>().unwrap().val, 10);
+        assert_eq!(locator.get::<MyStruct>().unwrap().val, 42);
+        assert_eq!(locator.get::<String>().unwrap(), "42");
+
+        let result =
+            locator.invoke(|s: String, my_struct: MyStruct| s.len() as i32 + my_struct.val);
+        assert_eq!(result.unwrap(), 44);
     }
 
     #[test]
@@ -210,6 +4130,160 @@ mod tests {
         assert_eq!(result, 42);
     }
 
+    #[test]
+    fn test_invoke_checked() {
+        let mut locator = Locator::new();
+        locator.insert(MyStruct { val: 42 });
+
+        let err = locator
+            .invoke_checked(|_: String, my_struct: MyStruct, _: u64| my_struct.val)
+            .unwrap_err();
+
+        let LocatorError::Missing { types } = err else {
+            panic!("expected missing error: {err}");
+        };
+        assert_eq!(types, vec![type_name::<String>(), type_name::<u64>()]);
+
+        locator.insert(String::from("hello"));
+        locator.insert(1_u64);
+        let result = locator.invoke_checked(|s: String, my_struct: MyStruct, n: u64| {
+            s.len() as i32 + my_struct.val + n as i32
+        });
+        assert_eq!(result.unwrap(), 48);
+    }
+
+    #[test]
+    fn test_invoke_many_args() {
+        let mut locator = Locator::new();
+        locator.insert(1_u8);
+        locator.insert(2_u16);
+        locator.insert(3_u32);
+        locator.insert(4_u64);
+        locator.insert(5_u128);
+        locator.insert(6_usize);
+        locator.insert(7_i8);
+        locator.insert(8_i16);
+        locator.insert(9_i32);
+        locator.insert(10_i64);
+        locator.insert(11_i128);
+        locator.insert(12_isize);
+        locator.insert(String::from("13"));
+        locator.insert(MyStruct { val: 14 });
+
+        let result = locator
+            .invoke(
+                |a: u8,
+                 b: u16,
+                 c: u32,
+                 d: u64,
+                 e: u128,
+                 f: usize,
+                 g: i8,
+                 h: i16,
+                 i: i32,
+                 j: i64,
+                 k: i128,
+                 l: isize,
+                 m: String,
+                 n: MyStruct| {
+                    a as i64
+                        + b as i64
+                        + c as i64
+                        + d as i64
+                        + e as i64
+                        + f as i64
+                        + g as i64
+                        + h as i64
+                        + i as i64
+                        + j
+                        + k as i64
+                        + l as i64
+                        + m.parse::<i64>().unwrap()
+                        + n.val as i64
+                },
+            )
+            .unwrap();
+
+        assert_eq!(result, (1..=14).sum());
+    }
+
+    #[test]
+    fn test_invoke_with_locator_ref() {
+        use crate::LocatorRef;
+
+        let mut locator = Locator::new();
+
+        locator.insert(MyStruct { val: 42 });
+        locator.insert_with(|_| String::from("hello"));
+
+        let result = locator
+            .invoke_ref(|l: LocatorRef, my_struct: &MyStruct| {
+                l.get::<String>().unwrap().len() as i32 + my_struct.val
+            })
+            .unwrap();
+
+        assert_eq!(result, 47);
+    }
+
+    #[test]
+    fn test_invoke_ref_with_locator_ref_resolving_owned_service() {
+        use crate::LocatorRef;
+
+        let mut locator = Locator::new();
+
+        locator.insert_with(|_| MyStruct { val: 42 });
+        locator.insert_with(|_| String::from("hello"));
+
+        let result = locator
+            .invoke_ref(|l: LocatorRef| {
+                l.get::<String>().unwrap().len() as i32 + l.get::<MyStruct>().unwrap().val
+            })
+            .unwrap();
+
+        assert_eq!(result, 47);
+
+        let same = locator
+            .invoke_ref(|l: LocatorRef| std::ptr::eq(&*l, &locator))
+            .unwrap();
+
+        assert!(same);
+    }
+
+    #[test]
+    fn test_locator_ref_resolves_same_scoped_value() {
+        use crate::LocatorRef;
+
+        let mut locator = Locator::new();
+        locator.register_scoped(|_| Arc::new(MyStruct { val: 42 }));
+
+        let value = locator.get::<Arc<MyStruct>>().unwrap();
+        let same = locator
+            .invoke_ref(|l: LocatorRef| Arc::ptr_eq(&value, &l.get::<Arc<MyStruct>>().unwrap()))
+            .unwrap();
+
+        assert!(same);
+    }
+
+    #[test]
+    fn test_invoke_ref() {
+        let mut locator = Locator::new();
+
+        locator.insert(MyStruct { val: 42 });
+        locator.insert(String::from("hello"));
+        locator.insert_with(|_| 10_i32);
+
+        let result = locator
+            .invoke_ref(|s: &String, my_struct: &MyStruct| s.len() as i32 + my_struct.val)
+            .unwrap();
+        assert_eq!(result, 47);
+
+        let err = locator.invoke_ref(|n: &i32| *n).unwrap_err();
+        assert!(err.to_string().contains("unable to borrow `i32`"));
+
+        let err = locator.invoke_ref(|n: &u64| *n).unwrap_err();
+        assert!(matches!(err, LocatorError::NotFound { .. }));
+    }
+
     #[tokio::test]
     async fn test_invoke_async() {
         let mut locator = Locator::new();
@@ -223,4 +4297,79 @@ mod tests {
 
         assert_eq!(result, 42);
     }
+
+    #[test]
+    fn test_try_invoke() {
+        let mut locator = Locator::new();
+        locator.insert(MyStruct { val: 42 });
+
+        let result = locator.try_invoke(|my_struct: MyStruct| Ok::<_, LocatorError>(my_struct.val));
+        assert_eq!(result.unwrap(), 42);
+
+        let result =
+            locator.try_invoke(|_: MyStruct| Err::<i32, _>(LocatorError::Other("failed".into())));
+        assert!(matches!(result, Err(LocatorError::Other(_))));
+
+        let result = locator.try_invoke(|s: String| Ok::<_, LocatorError>(s));
+        assert!(matches!(result, Err(LocatorError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_try_invoke_async() {
+        let mut locator = Locator::new();
+        locator.insert(MyStruct { val: 42 });
+
+        let result = locator
+            .try_invoke_async(
+                |my_struct: MyStruct| async move { Ok::<_, LocatorError>(my_struct.val) },
+            )
+            .await;
+        assert_eq!(result.unwrap(), 42);
+
+        let result = locator
+            .try_invoke_async(|_: MyStruct| async move {
+                Err::<i32, _>(LocatorError::Other("failed".into()))
+            })
+            .await;
+        assert!(matches!(result, Err(LocatorError::Other(_))));
+
+        let result = locator
+            .try_invoke_async(|s: String| async move { Ok::<_, LocatorError>(s) })
+            .await;
+        assert!(matches!(result, Err(LocatorError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_try_invoke_async_with_fallible_services() {
+        use crate::try_locator::TryLocator;
+
+        #[derive(Debug, Clone)]
+        struct Pool;
+
+        let mut locator = Locator::new();
+        locator.insert(MyStruct { val: 42 });
+        locator.try_insert_with::<_, Pool>(|_| Err(LocatorError::other("database is down")));
+
+        // The error of the service fails the injection.
+        let result = locator
+            .try_invoke_async(|_: MyStruct, _: Pool| async move { Ok::<_, LocatorError>(()) })
+            .await;
+        let err = result.unwrap_err();
+        assert_eq!(err.to_string(), "database is down");
+
+        locator.try_insert_with::<_, Pool>(|_| Ok(Pool));
+
+        // The services are injected and the error of the body is returned.
+        let result = locator
+            .try_invoke_async(|s: MyStruct, _: Pool| async move {
+                Err::<i32, _>(LocatorError::other(format!("invalid value {}", s.val)))
+            })
+            .await;
+        assert_eq!(result.unwrap_err().to_string(), "invalid value 42");
+
+        let result = locator
+            .try_invoke_async(|s: MyStruct, _: Pool| async move { Ok::<_, LocatorError>(s.val) })
+            .await;
+        assert_eq!(result.unwrap(), 42);
+    }
 }
@@ -0,0 +1,122 @@
+/// Registers a concrete type as a boxed trait object, the service is resolved as `Box<dyn Trait>`.
+///
+/// The value form clones the value on each resolution, the factory form takes a closure
+/// that receives the `Locator` and returns the concrete type.
+///
+/// ```
+/// use kizuna::{insert_trait, Locator};
+///
+/// trait Greeter {
+///     fn greet(&self) -> String;
+/// }
+///
+/// #[derive(Clone)]
+/// struct English;
+///
+/// impl Greeter for English {
+///     fn greet(&self) -> String {
+///         String::from("hello")
+///     }
+/// }
+///
+/// let mut locator = Locator::new();
+/// insert_trait!(locator, dyn Greeter + Send + Sync, English);
+///
+/// let greeter = locator.get::<Box<dyn Greeter + Send + Sync>>().unwrap();
+/// assert_eq!(greeter.greet(), "hello");
+/// ```
+#[macro_export]
+macro_rules! insert_trait {
+    ($locator:expr, $trait:ty, |$arg:pat_param| $body:expr) => {
+        $locator
+            .insert_with::<_, ::std::boxed::Box<$trait>>(move |$arg| ::std::boxed::Box::new($body))
+    };
+    ($locator:expr, $trait:ty, $value:expr) => {{
+        let value = $value;
+        $locator.insert_with::<_, ::std::boxed::Box<$trait>>(move |_| {
+            ::std::boxed::Box::new(::std::clone::Clone::clone(&value))
+        })
+    }};
+}
+
+/// Registers a concrete type as an implementor of a trait object, see `Locator::register_impl`.
+///
+/// Like `insert_trait!` it takes a value that is cloned on each resolution or a factory closure.
+///
+/// ```
+/// use kizuna::{register_impl, Locator};
+///
+/// trait Plugin: Send + Sync {}
+///
+/// #[derive(Clone)]
+/// struct Auth;
+/// impl Plugin for Auth {}
+///
+/// #[derive(Clone)]
+/// struct Logger;
+/// impl Plugin for Logger {}
+///
+/// let mut locator = Locator::new();
+/// register_impl!(locator, dyn Plugin, Auth);
+/// register_impl!(locator, dyn Plugin, |_| Logger);
+///
+/// assert_eq!(locator.resolve_impls::<dyn Plugin>().len(), 2);
+/// ```
+#[macro_export]
+macro_rules! register_impl {
+    ($locator:expr, $trait:ty, |$arg:pat_param| $body:expr) => {
+        $locator.register_impl::<$trait, _>(move |$arg| ::std::boxed::Box::new($body))
+    };
+    ($locator:expr, $trait:ty, $value:expr) => {{
+        let value = $value;
+        $locator.register_impl::<$trait, _>(move |_| {
+            ::std::boxed::Box::new(::std::clone::Clone::clone(&value))
+        })
+    }};
+}
+
+/// Registers a concrete type as a boxed trait object resolving its dependencies from the locator,
+/// the service is resolved as `Box<dyn Trait + Bounds>`.
+///
+/// The dependencies are the typed arguments of the closure, each resolved on every resolution.
+/// The auto trait bounds after the `:` are added to the trait object, any number can be used.
+///
+/// If a dependency cannot be resolved the service is not resolved either, `get` returns `None`
+/// and `resolve` returns the error of the dependency.
+///
+/// ```
+/// use kizuna::{bind_trait, Locator};
+///
+/// trait Greeter {
+///     fn greet(&self) -> String;
+/// }
+///
+/// struct Localized(String);
+///
+/// impl Greeter for Localized {
+///     fn greet(&self) -> String {
+///         self.0.clone()
+///     }
+/// }
+///
+/// let mut locator = Locator::new();
+/// locator.insert(String::from("hola"));
+/// bind_trait!(locator, Greeter: Send + Sync, from |greeting: String| Localized(greeting));
+///
+/// let greeter = locator.get::<Box<dyn Greeter + Send + Sync>>().unwrap();
+/// assert_eq!(greeter.greet(), "hola");
+/// ```
+#[macro_export]
+macro_rules! bind_trait {
+    ($locator:expr, $trait:path $(: $bound:ident $(+ $bounds:ident)*)?, from || $body:expr) => {
+        $crate::bind_trait!($locator, $trait $(: $bound $(+ $bounds)*)?, from | | $body)
+    };
+    ($locator:expr, $trait:path $(: $bound:ident $(+ $bounds:ident)*)?, from |$($arg:ident : $ty:ty),*| $body:expr) => {
+        $locator.insert_fallible_with::<_, ::std::boxed::Box<dyn $trait $(+ $bound $(+ $bounds)*)?>>(move |locator| {
+            $(
+                let $arg = locator.resolve::<$ty>()?;
+            )*
+            ::std::result::Result::Ok(::std::boxed::Box::new($body))
+        })
+    };
+}
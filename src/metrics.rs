@@ -0,0 +1,7 @@
+use std::time::Duration;
+
+/// Receives the metrics of the services resolved by a `Locator`, see `Locator::with_metrics`.
+pub trait MetricsCollector: Send + Sync {
+    /// Records that a service of the given type was resolved and how long it took.
+    fn record(&self, type_name: &'static str, elapsed: Duration);
+}
@@ -0,0 +1,49 @@
+use crate::{Locator, LocatorError, Provider};
+use std::{
+    any::type_name,
+    sync::{Mutex, PoisonError},
+};
+
+impl Locator {
+    /// Creates an empty `Locator` to register the services used by a test.
+    pub fn mock() -> Self {
+        Locator::new()
+    }
+
+    /// Inserts a value of type `T` that doesn't need to implement `Clone`.
+    ///
+    /// The value is moved out on the first resolution, the next resolutions fail with
+    /// `LocatorError::Other` naming the service, use `insert` for values that are resolved more than once.
+    /// The value must still be `Send + Sync` because the locator can be shared between threads.
+    pub fn mock_insert<T>(&mut self, value: T) -> Option<Provider>
+    where
+        T: Send + Sync + 'static,
+    {
+        let value = Mutex::new(Some(value));
+        self.insert_fallible_with(move |_| {
+            let value = value.lock().unwrap_or_else(PoisonError::into_inner).take();
+            value.ok_or_else(|| {
+                LocatorError::other(format!(
+                    "mocked `{}` was already resolved, it can only be resolved once",
+                    type_name::<T>()
+                ))
+            })
+        })
+    }
+
+    /// Returns the value of type `T`, panics if it cannot be resolved.
+    #[track_caller]
+    pub fn expect_resolved<T>(&self) -> T
+    where
+        T: Send + Sync + 'static,
+    {
+        match self.get::<T>() {
+            Some(value) => value,
+            None => panic!(
+                "expected `{}` to be resolved: {}",
+                type_name::<T>(),
+                self.not_found::<T>()
+            ),
+        }
+    }
+}
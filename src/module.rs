@@ -0,0 +1,10 @@
+use crate::Locator;
+use std::collections::hash_map::RandomState;
+
+/// A group of related services that can be registered together using `Locator::install`.
+///
+/// The type parameter is the hasher of the locator, see `Locator<S>`.
+pub trait Module<S = RandomState> {
+    /// Registers the services of this module into the given `Locator`.
+    fn register(&self, locator: &mut Locator<S>);
+}
@@ -0,0 +1,39 @@
+use std::{
+    fmt::{Debug, Formatter},
+    sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+/// A service that can be mutated in place, all the clones share the same value.
+///
+/// Services inserted using `Locator::insert_mut` are resolved as `Mut<T>`,
+/// so they can be used as arguments of `Locator::invoke`.
+pub struct Mut<T>(Arc<RwLock<T>>);
+
+impl<T> Mut<T> {
+    /// Creates a new `Mut` with the given value.
+    pub fn new(value: T) -> Self {
+        Mut(Arc::new(RwLock::new(value)))
+    }
+
+    /// Locks the value for reading.
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Locks the value for writing.
+    pub fn write(&self) -> RwLockWriteGuard<'_, T> {
+        self.0.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T> Clone for Mut<T> {
+    fn clone(&self) -> Self {
+        Mut(self.0.clone())
+    }
+}
+
+impl<T: Debug> Debug for Mut<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Mut").field(&*self.read()).finish()
+    }
+}
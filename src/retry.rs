@@ -0,0 +1,104 @@
+use crate::LocatorError;
+use std::{
+    fmt::{Debug, Formatter},
+    future::Future,
+    sync::Arc,
+    time::Duration,
+};
+
+/// How many times a fallible async factory is run until it succeeds,
+/// see `TryLocator::try_insert_async_with_retry`.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    delay: Duration,
+    multiplier: u32,
+    sleep: Arc<dyn Fn(Duration) + Send + Sync>,
+}
+
+impl RetryPolicy {
+    /// Creates a policy that runs the factory at most `max_attempts` times without waiting between attempts.
+    ///
+    /// # Panics
+    /// If `max_attempts` is zero.
+    pub fn new(max_attempts: u32) -> Self {
+        assert!(max_attempts > 0, "max attempts must be greater than zero");
+
+        RetryPolicy {
+            max_attempts,
+            delay: Duration::ZERO,
+            multiplier: 1,
+            sleep: Arc::new(std::thread::sleep),
+        }
+    }
+
+    /// Waits the given duration before retrying a failed attempt.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Multiplies the delay by the given value after each failed attempt, for an exponential backoff.
+    pub fn with_multiplier(mut self, multiplier: u32) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Sets the function that waits the delay between attempts, `std::thread::sleep` by default.
+    ///
+    /// The function is called from the task resolving the service, so it blocks that task while it waits.
+    pub fn with_sleep<F>(mut self, sleep: F) -> Self
+    where
+        F: Fn(Duration) + Send + Sync + 'static,
+    {
+        self.sleep = Arc::new(sleep);
+        self
+    }
+
+    /// Returns the max number of times the factory runs.
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Runs `f` until it succeeds or the attempts are exhausted, returns the last error.
+    pub(crate) async fn run<T, F, Fut>(&self, mut f: F) -> Result<T, LocatorError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, LocatorError>>,
+    {
+        let mut delay = self.delay;
+        let mut attempt = 1;
+
+        loop {
+            match f().await {
+                Ok(value) => return Ok(value),
+                Err(err) if attempt >= self.max_attempts => return Err(err),
+                Err(_) => {
+                    if !delay.is_zero() {
+                        (self.sleep)(delay);
+                    }
+
+                    delay = delay.saturating_mul(self.multiplier);
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+impl Debug for RetryPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("delay", &self.delay)
+            .field("multiplier", &self.multiplier)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Runs the factory up to three times.
+    fn default() -> Self {
+        RetryPolicy::new(3)
+    }
+}
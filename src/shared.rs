@@ -0,0 +1,225 @@
+use crate::{Locator, LocatorError, Provider};
+use std::{
+    cell::RefCell,
+    collections::hash_map::RandomState,
+    hash::BuildHasher,
+    sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+thread_local! {
+    // The addresses of the shared locators resolving a service in the current thread.
+    static RESOLVING: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A `Locator` that can be shared between threads and modified after it was created,
+/// all the clones share the same locator.
+///
+/// `get` takes a read lock and `insert` or `remove` take a write lock for the duration of the call.
+///
+/// # Deadlocks
+/// Factories run while the read lock is held, so a factory must not insert or remove services
+/// through a `SharedLocator` of the same locator, use the `&Locator` it receives to resolve its dependencies.
+///
+/// Those writes fail with `LocatorError::ReentrantMutation` if the service is resolved with `get`,
+/// the guard returned by `read` is not tracked.
+pub struct SharedLocator<S = RandomState>(Arc<RwLock<Locator<S>>>);
+
+impl<S> SharedLocator<S> {
+    /// Creates a `SharedLocator` from the given locator.
+    pub fn new(locator: Locator<S>) -> Self {
+        SharedLocator(Arc::new(RwLock::new(locator)))
+    }
+
+    /// Locks the locator for reading.
+    pub fn read(&self) -> RwLockReadGuard<'_, Locator<S>> {
+        self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Locks the locator for writing.
+    ///
+    /// # Panics
+    /// If a factory calls this while a service is resolved from this locator, see `try_write`.
+    pub fn write(&self) -> RwLockWriteGuard<'_, Locator<S>> {
+        match self.try_write() {
+            Ok(locator) => locator,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Locks the locator for writing, fails with `LocatorError::ReentrantMutation`
+    /// if a service is being resolved from this locator in the current thread.
+    pub fn try_write(&self) -> Result<RwLockWriteGuard<'_, Locator<S>>, LocatorError> {
+        if self.is_resolving() {
+            return Err(LocatorError::ReentrantMutation);
+        }
+
+        Ok(self.0.write().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn id(&self) -> usize {
+        Arc::as_ptr(&self.0) as *const () as usize
+    }
+
+    fn is_resolving(&self) -> bool {
+        let id = self.id();
+        RESOLVING.with(|resolving| resolving.borrow().contains(&id))
+    }
+
+    /// Runs `f` with the locator locked for reading, marking it as resolving in the current thread.
+    fn resolving<R>(&self, f: impl FnOnce(&Locator<S>) -> R) -> R {
+        // Unmarks the locator even if the closure panics.
+        struct Pop;
+
+        impl Drop for Pop {
+            fn drop(&mut self) {
+                RESOLVING.with(|resolving| resolving.borrow_mut().pop());
+            }
+        }
+
+        let locator = self.read();
+        RESOLVING.with(|resolving| resolving.borrow_mut().push(self.id()));
+        let _pop = Pop;
+        f(&locator)
+    }
+}
+
+impl<S: BuildHasher> SharedLocator<S> {
+    /// Inserts a value of type `T`, see `Locator::insert`.
+    ///
+    /// Fails with `LocatorError::ReentrantMutation` if called by a factory of this locator.
+    pub fn insert<T>(&self, value: T) -> Result<Option<Provider<S>>, LocatorError>
+    where
+        T: Send + Sync + Clone + 'static,
+    {
+        Ok(self.try_write()?.insert(value))
+    }
+
+    /// Inserts a value of type `T` using a factory, see `Locator::insert_with`.
+    ///
+    /// Fails with `LocatorError::ReentrantMutation` if called by a factory of this locator.
+    pub fn insert_with<F, T>(&self, factory: F) -> Result<Option<Provider<S>>, LocatorError>
+    where
+        F: Fn(&Locator<S>) -> T + 'static + Send + Sync,
+        T: Send + Sync + 'static,
+    {
+        Ok(self.try_write()?.insert_with(factory))
+    }
+
+    /// Returns a value of type `T`, see `Locator::get`.
+    pub fn get<T>(&self) -> Option<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.resolving(|locator| locator.get::<T>())
+    }
+
+    /// Returns `true` if the locator contains a value of type `T`, see `Locator::contains`.
+    pub fn contains<T>(&self) -> bool
+    where
+        T: Send + Sync + 'static,
+    {
+        self.read().contains::<T>()
+    }
+
+    /// Removes the value of type `T`, see `Locator::remove`.
+    ///
+    /// Fails with `LocatorError::ReentrantMutation` if called by a factory of this locator.
+    pub fn remove<T>(&self) -> Result<Option<Provider<S>>, LocatorError>
+    where
+        T: Send + Sync + 'static,
+    {
+        Ok(self.try_write()?.remove::<T>())
+    }
+}
+
+impl<S> Clone for SharedLocator<S> {
+    fn clone(&self) -> Self {
+        SharedLocator(self.0.clone())
+    }
+}
+
+impl<S: Default> Default for SharedLocator<S> {
+    fn default() -> Self {
+        SharedLocator::new(Locator::default())
+    }
+}
+
+impl<S> From<Locator<S>> for SharedLocator<S> {
+    fn from(locator: Locator<S>) -> Self {
+        SharedLocator::new(locator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SharedLocator;
+    use crate::{Locator, LocatorError, Provider};
+    use std::thread;
+
+    #[test]
+    fn test_concurrent_reads() {
+        let mut locator = Locator::new();
+        locator.insert(String::from("hello"));
+        let shared = SharedLocator::new(locator);
+
+        let handles = (0..8)
+            .map(|_| {
+                let shared = shared.clone();
+                thread::spawn(move || shared.get::<String>().unwrap())
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), "hello");
+        }
+    }
+
+    #[test]
+    fn test_serialized_writes() {
+        let shared: SharedLocator = SharedLocator::default();
+        shared.insert(0_usize).unwrap();
+
+        let handles = (0..8)
+            .map(|_| {
+                let shared = shared.clone();
+                thread::spawn(move || {
+                    for _ in 0..100 {
+                        let mut locator = shared.write();
+                        let count = locator.get::<usize>().unwrap();
+                        locator.insert(count + 1);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(shared.get::<usize>().unwrap(), 800);
+
+        shared.remove::<usize>().unwrap();
+        assert!(!shared.contains::<usize>());
+    }
+
+    #[test]
+    fn test_reentrant_mutation() {
+        let shared: SharedLocator = SharedLocator::default();
+        shared
+            .insert_with({
+                let shared = shared.clone();
+                move |_| shared.insert(String::from("hello"))
+            })
+            .unwrap();
+
+        let result = shared
+            .get::<Result<Option<Provider>, LocatorError>>()
+            .unwrap();
+        assert!(matches!(result, Err(LocatorError::ReentrantMutation)));
+
+        // The locator can be modified after the resolution.
+        assert!(!shared.contains::<String>());
+        shared.insert(String::from("hello")).unwrap();
+        assert_eq!(shared.get::<String>().unwrap(), "hello");
+    }
+}
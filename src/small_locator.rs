@@ -0,0 +1,237 @@
+#![allow(clippy::type_complexity)]
+
+use crate::Singleton;
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt::{Debug, Formatter},
+    sync::Arc,
+};
+
+/// The number of services a `SmallLocator` stores in a `Vec` before switching to a `HashMap`.
+pub const SMALL_LOCATOR_THRESHOLD: usize = 8;
+
+#[derive(Clone)]
+enum SmallProvider {
+    Single(Singleton),
+    Factory(Arc<dyn Fn(&SmallLocator) -> Box<dyn Any + Send + Sync> + Send + Sync>),
+}
+
+#[derive(Clone)]
+enum Store {
+    Small(Vec<(TypeId, SmallProvider, &'static str)>),
+    Map(HashMap<TypeId, (SmallProvider, &'static str)>),
+}
+
+/// A service locator optimized for a few services.
+///
+/// Up to `SMALL_LOCATOR_THRESHOLD` services are stored in a `Vec` and found with a linear scan,
+/// which is faster than hashing the `TypeId` for small sizes. When more services are inserted
+/// the locator switches to a `HashMap` and stays that way.
+/// The services resolve the same way they do in a `Locator`.
+#[derive(Clone)]
+pub struct SmallLocator {
+    store: Store,
+}
+
+impl Default for SmallLocator {
+    fn default() -> Self {
+        SmallLocator {
+            store: Store::Small(Vec::new()),
+        }
+    }
+}
+
+impl SmallLocator {
+    /// Creates a new `SmallLocator`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn insert_provider(&mut self, id: TypeId, provider: SmallProvider, type_name: &'static str) {
+        match &mut self.store {
+            Store::Small(entries) => {
+                if let Some(entry) = entries.iter_mut().find(|(key, _, _)| *key == id) {
+                    *entry = (id, provider, type_name);
+                } else if entries.len() < SMALL_LOCATOR_THRESHOLD {
+                    entries.push((id, provider, type_name));
+                } else {
+                    let mut map = entries
+                        .drain(..)
+                        .map(|(key, provider, name)| (key, (provider, name)))
+                        .collect::<HashMap<_, _>>();
+
+                    map.insert(id, (provider, type_name));
+                    self.store = Store::Map(map);
+                }
+            }
+            Store::Map(map) => {
+                map.insert(id, (provider, type_name));
+            }
+        }
+    }
+
+    fn get_provider(&self, id: &TypeId) -> Option<&SmallProvider> {
+        match &self.store {
+            Store::Small(entries) => entries
+                .iter()
+                .find(|(key, _, _)| key == id)
+                .map(|(_, provider, _)| provider),
+            Store::Map(map) => map.get(id).map(|(provider, _)| provider),
+        }
+    }
+
+    /// Inserts a value of type `T` into the `SmallLocator`.
+    pub fn insert<T>(&mut self, value: T)
+    where
+        T: Send + Sync + Clone + 'static,
+    {
+        let provider = SmallProvider::Single(Singleton::from_value(value));
+        self.insert_provider(TypeId::of::<T>(), provider, std::any::type_name::<T>());
+    }
+
+    /// Inserts a value of type `T` into the `SmallLocator` using a factory function that takes a `SmallLocator` as input.
+    pub fn insert_with<F, T>(&mut self, factory: F)
+    where
+        F: Fn(&Self) -> T + 'static + Send + Sync,
+        T: Send + Sync + 'static,
+    {
+        let provider = SmallProvider::Factory(Arc::new(move |locator| Box::new(factory(locator))));
+        self.insert_provider(TypeId::of::<T>(), provider, std::any::type_name::<T>());
+    }
+
+    /// Returns a value of type `T` from the `SmallLocator` if it exists.
+    pub fn get<T>(&self) -> Option<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        let value = match self.get_provider(&TypeId::of::<T>())? {
+            SmallProvider::Single(singleton) => singleton.get(),
+            SmallProvider::Factory(f) => f(self),
+        };
+
+        value.downcast::<T>().map(|x| *x).ok()
+    }
+
+    /// Returns `true` if the `SmallLocator` contains a value of type `T`.
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.get_provider(&TypeId::of::<T>()).is_some()
+    }
+
+    /// Removes the value of type `T` from the `SmallLocator`, returns `true` if it was removed.
+    pub fn remove<T: 'static>(&mut self) -> bool {
+        let id = TypeId::of::<T>();
+        match &mut self.store {
+            Store::Small(entries) => match entries.iter().position(|(key, _, _)| *key == id) {
+                Some(index) => {
+                    entries.remove(index);
+                    true
+                }
+                None => false,
+            },
+            Store::Map(map) => map.remove(&id).is_some(),
+        }
+    }
+
+    /// Returns the number of services in the `SmallLocator`.
+    pub fn len(&self) -> usize {
+        match &self.store {
+            Store::Small(entries) => entries.len(),
+            Store::Map(map) => map.len(),
+        }
+    }
+
+    /// Returns `true` if the `SmallLocator` contains no services.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the services are stored in a `HashMap`.
+    pub fn is_promoted(&self) -> bool {
+        matches!(self.store, Store::Map(_))
+    }
+}
+
+impl Debug for SmallLocator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut names = match &self.store {
+            Store::Small(entries) => entries.iter().map(|(_, _, name)| *name).collect::<Vec<_>>(),
+            Store::Map(map) => map.values().map(|(_, name)| *name).collect(),
+        };
+        names.sort_unstable();
+
+        f.debug_struct("SmallLocator")
+            .field("services", &names)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Service<const N: usize>;
+
+    #[test]
+    fn test_insert_and_get() {
+        let mut locator = SmallLocator::new();
+        locator.insert(String::from("hello"));
+        locator.insert_with(|locator| locator.get::<String>().unwrap().len());
+
+        assert_eq!(locator.get::<String>().unwrap(), "hello");
+        assert_eq!(locator.get::<usize>().unwrap(), 5);
+        assert!(locator.get::<i32>().is_none());
+
+        locator.insert(String::from("world"));
+        assert_eq!(locator.get::<String>().unwrap(), "world");
+        assert_eq!(locator.len(), 2);
+    }
+
+    #[test]
+    fn test_at_threshold() {
+        let mut locator = SmallLocator::new();
+        locator.insert(Service::<0>);
+        locator.insert(Service::<1>);
+        locator.insert(Service::<2>);
+        locator.insert(Service::<3>);
+        locator.insert(Service::<4>);
+        locator.insert(Service::<5>);
+        locator.insert(Service::<6>);
+        locator.insert(Service::<7>);
+
+        assert_eq!(locator.len(), SMALL_LOCATOR_THRESHOLD);
+        assert!(!locator.is_promoted());
+
+        // Replacing a service doesn't grow the locator.
+        locator.insert(Service::<7>);
+        assert!(!locator.is_promoted());
+
+        assert_eq!(locator.get::<Service<0>>(), Some(Service::<0>));
+        assert_eq!(locator.get::<Service<7>>(), Some(Service::<7>));
+    }
+
+    #[test]
+    fn test_beyond_threshold() {
+        let mut locator = SmallLocator::new();
+        locator.insert(Service::<0>);
+        locator.insert(Service::<1>);
+        locator.insert(Service::<2>);
+        locator.insert(Service::<3>);
+        locator.insert(Service::<4>);
+        locator.insert(Service::<5>);
+        locator.insert(Service::<6>);
+        locator.insert(Service::<7>);
+        locator.insert_with(|locator| locator.get::<Service<0>>().map(|_| 8_usize));
+
+        assert!(locator.is_promoted());
+        assert_eq!(locator.len(), SMALL_LOCATOR_THRESHOLD + 1);
+        assert_eq!(locator.get::<Service<0>>(), Some(Service::<0>));
+        assert_eq!(locator.get::<Service<7>>(), Some(Service::<7>));
+        assert_eq!(locator.get::<Option<usize>>(), Some(Some(8)));
+
+        assert!(locator.remove::<Service<3>>());
+        assert!(!locator.contains::<Service<3>>());
+        assert_eq!(locator.len(), SMALL_LOCATOR_THRESHOLD);
+    }
+}
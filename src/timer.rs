@@ -0,0 +1,136 @@
+//! A sleep that doesn't depend on an async runtime, see `Locator::get_async_timeout`.
+//!
+//! Each sleep waits in its own thread, which stops when the deadline passes or the sleep is dropped.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        mpsc::{self, RecvTimeoutError, Sender},
+        Arc, Mutex, PoisonError,
+    },
+    task::{Context, Poll, Waker},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+#[derive(Default)]
+struct State {
+    done: bool,
+    waker: Option<Waker>,
+}
+
+/// The thread waiting for the deadline of a `Sleep`.
+struct Waiter {
+    // Dropping the sender wakes the thread before the deadline.
+    cancel: Sender<()>,
+    thread: JoinHandle<()>,
+}
+
+/// A future that completes after the duration.
+pub(crate) struct Sleep {
+    deadline: Instant,
+    state: Arc<Mutex<State>>,
+    waiter: Option<Waiter>,
+}
+
+/// Returns a future that completes after the given duration.
+pub(crate) fn sleep(duration: Duration) -> Sleep {
+    Sleep {
+        deadline: Instant::now() + duration,
+        state: Arc::default(),
+        waiter: None,
+    }
+}
+
+impl Sleep {
+    fn spawn_waiter(&self) -> Waiter {
+        let (cancel, cancelled) = mpsc::channel::<()>();
+        let deadline = self.deadline;
+        let state = self.state.clone();
+
+        let thread = thread::spawn(move || {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            if let Err(RecvTimeoutError::Timeout) = cancelled.recv_timeout(timeout) {
+                let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+                state.done = true;
+                if let Some(waker) = state.waker.take() {
+                    waker.wake();
+                }
+            }
+        });
+
+        Waiter { cancel, thread }
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if Instant::now() >= self.deadline {
+            return Poll::Ready(());
+        }
+
+        {
+            let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+            if state.done {
+                return Poll::Ready(());
+            }
+
+            state.waker = Some(cx.waker().clone());
+        }
+
+        if self.waiter.is_none() {
+            self.waiter = Some(self.spawn_waiter());
+        }
+
+        Poll::Pending
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Some(Waiter { cancel, thread }) = self.waiter.take() {
+            drop(cancel);
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sleep;
+    use std::{
+        future::Future,
+        time::{Duration, Instant},
+    };
+
+    #[tokio::test]
+    async fn test_sleep() {
+        let start = Instant::now();
+        let long = sleep(Duration::from_millis(50));
+        let short = sleep(Duration::from_millis(10));
+
+        short.await;
+        assert!(start.elapsed() >= Duration::from_millis(10));
+
+        long.await;
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn test_drop_sleep_before_deadline() {
+        let start = Instant::now();
+        let mut sleep = Box::pin(sleep(Duration::from_secs(60)));
+
+        std::future::poll_fn(|cx| {
+            assert!(sleep.as_mut().poll(cx).is_pending());
+            std::task::Poll::Ready(())
+        })
+        .await;
+
+        drop(sleep);
+        assert!(start.elapsed() < Duration::from_secs(60));
+    }
+}
@@ -0,0 +1,58 @@
+use crate::Locator;
+use http::Request;
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// A layer that inserts an `Arc<Locator>` into the extensions of each request.
+#[derive(Clone)]
+pub struct LocatorLayer {
+    locator: Arc<Locator>,
+}
+
+impl LocatorLayer {
+    /// Creates a new `LocatorLayer` that injects the given locator.
+    pub fn new(locator: Arc<Locator>) -> Self {
+        LocatorLayer { locator }
+    }
+}
+
+impl<S> Layer<S> for LocatorLayer {
+    type Service = LocatorService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        LocatorService {
+            inner,
+            locator: self.locator.clone(),
+        }
+    }
+}
+
+/// A service that inserts an `Arc<Locator>` into the extensions of each request before calling the inner service.
+#[derive(Clone)]
+pub struct LocatorService<S> {
+    inner: S,
+    locator: Arc<Locator>,
+}
+
+impl<S, B> Service<Request<B>> for LocatorService<S>
+where
+    S: Service<Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<B>) -> Self::Future {
+        req.extensions_mut().insert(self.locator.clone());
+        self.inner.call(req)
+    }
+}
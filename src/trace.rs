@@ -0,0 +1,73 @@
+//! Spans and events emitted while resolving services when the `tracing` feature is enabled,
+//! otherwise everything here compiles to nothing.
+
+use std::future::Future;
+
+/// Tracks the resolution of a service, nested resolutions are recorded inside its span.
+#[cfg(feature = "tracing")]
+pub(crate) struct Resolving {
+    name: &'static str,
+    start: std::time::Instant,
+    _span: tracing::span::EnteredSpan,
+}
+
+#[cfg(feature = "tracing")]
+impl Resolving {
+    pub(crate) fn start(name: &'static str) -> Self {
+        let span = tracing::debug_span!("resolve", service = name).entered();
+        tracing::trace!("resolving {}", name);
+
+        Resolving {
+            name,
+            start: std::time::Instant::now(),
+            _span: span,
+        }
+    }
+
+    pub(crate) fn finish(self, resolved: bool) {
+        if resolved {
+            tracing::debug!("resolved {} in {:?}", self.name, self.start.elapsed());
+        } else {
+            tracing::debug!("unable to resolve {}", self.name);
+        }
+    }
+}
+
+/// Tracks the resolution of a service, nested resolutions are recorded inside its span.
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Resolving;
+
+#[cfg(not(feature = "tracing"))]
+impl Resolving {
+    #[inline(always)]
+    pub(crate) fn start(_name: &'static str) -> Self {
+        Resolving
+    }
+
+    #[inline(always)]
+    pub(crate) fn finish(self, _resolved: bool) {}
+}
+
+/// Awaits the future of an async factory of `T` inside a resolution span.
+#[cfg(feature = "tracing")]
+pub(crate) async fn resolve_async<T, F: Future>(fut: F) -> F::Output {
+    use tracing::Instrument;
+
+    let name = std::any::type_name::<T>();
+    let span = tracing::debug_span!("resolve", service = name);
+    let start = std::time::Instant::now();
+
+    span.in_scope(|| tracing::trace!("resolving {}", name));
+    let value = fut.instrument(span.clone()).await;
+    span.in_scope(|| tracing::debug!("resolved {} in {:?}", name, start.elapsed()));
+
+    value
+}
+
+/// Awaits the future of an async factory of `T` inside a resolution span.
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+#[allow(clippy::extra_unused_type_parameters)]
+pub(crate) async fn resolve_async<T, F: Future>(fut: F) -> F::Output {
+    fut.await
+}
@@ -1,70 +1,244 @@
-use crate::{Locator, LocatorError, Provider};
-use std::any::TypeId;
+use crate::{cycle, Locator, LocatorError, Provider, RetryPolicy};
+use std::{
+    any::{Any, TypeId},
+    future::Future,
+    hash::BuildHasher,
+    sync::{Arc, OnceLock},
+};
 
 /// A locator that may fail to resolve a service.
 pub trait TryLocator: sealed::Sealed {
+    /// The hasher of the locator.
+    type Hasher;
+
     /// Attempts to insert a service that may fail to resolve.
-    fn try_insert_with<F, T>(&mut self, factory: F) -> Option<Provider>
+    ///
+    /// The first value created successfully is cached and cloned on each resolution,
+    /// the factory runs again while it fails. If the value is resolved concurrently for
+    /// the first time the factory may run more than once, but only one value is kept.
+    ///
+    /// The factory receives the `Locator` to resolve its dependencies, the services resolved
+    /// by the factory are tracked in the same thread, so resolving the service while it is
+    /// being created fails with `LocatorError::Cyclic`.
+    fn try_insert_with<F, T>(&mut self, factory: F) -> Option<Provider<Self::Hasher>>
     where
-        F: Fn(&Self) -> Result<T, LocatorError> + Send + Sync + 'static,
-        T: Send + Sync + 'static;
+        F: Fn(&Locator<Self::Hasher>) -> Result<T, LocatorError> + Send + Sync + 'static,
+        T: Send + Sync + Clone + 'static;
+
+    /// Attempts to insert a service using a factory that fails with its own error type,
+    /// the error is converted into a `LocatorError` when the service is resolved.
+    ///
+    /// The service is resolved using `try_get::<T>()` the same as the ones inserted by `try_insert_with`.
+    fn try_insert_with_err<F, T, E>(&mut self, factory: F) -> Option<Provider<Self::Hasher>>
+    where
+        F: Fn(&Locator<Self::Hasher>) -> Result<T, E> + Send + Sync + 'static,
+        T: Send + Sync + Clone + 'static,
+        E: Into<LocatorError>;
 
     /// Returns a service inserted by `try_insert_with` or fail if cannot be resolved.
+    ///
+    /// When called from the factory of other service inserted by `try_insert_with`, the error is wrapped in
+    /// `LocatorError::WhileResolving` so the error returned to the caller contains the chain of services.
+    ///
+    /// If there is no fallible service of type `T` the services inserted with `insert` or `insert_with`
+    /// are returned as `Ok`. Services inserted by `try_insert_async_with` are not found by this method.
     fn try_get<T>(&self) -> Result<T, LocatorError>
     where
         T: Send + Sync + 'static;
+
+    /// Attempts to insert a service that may fail to resolve using an async factory.
+    fn try_insert_async_with<F, Fut, T>(&mut self, factory: F) -> Option<Provider<Self::Hasher>>
+    where
+        F: Fn(&Locator<Self::Hasher>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, LocatorError>> + Send + 'static,
+        T: Send + Sync + 'static;
+
+    /// Attempts to insert a service that may fail to resolve using an async factory
+    /// that is run again on failure following the given policy.
+    ///
+    /// When all the attempts of a resolution fail the last error is returned, and the factory is retried
+    /// on the next `try_get_async`. The first value created successfully is cached and cloned on each resolution.
+    fn try_insert_async_with_retry<F, Fut, T>(
+        &mut self,
+        factory: F,
+        policy: RetryPolicy,
+    ) -> Option<Provider<Self::Hasher>>
+    where
+        F: Fn(&Locator<Self::Hasher>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, LocatorError>> + Send + 'static,
+        T: Send + Sync + Clone + 'static;
+
+    /// Returns a service inserted by `try_insert_with` or `try_insert_async_with` or fail if cannot be resolved.
+    ///
+    /// If there is no fallible service of type `T` the services inserted with `insert`, `insert_with`
+    /// or `insert_async_with` are returned as `Ok`.
+    fn try_get_async<T>(&self) -> impl Future<Output = Result<T, LocatorError>> + Send
+    where
+        T: Send + Sync + 'static;
 }
 
-impl TryLocator for Locator {
-    fn try_insert_with<F, T>(&mut self, factory: F) -> Option<Provider>
+impl<S> TryLocator for Locator<S>
+where
+    S: BuildHasher + Send + Sync,
+{
+    type Hasher = S;
+
+    fn try_insert_with<F, T>(&mut self, factory: F) -> Option<Provider<S>>
     where
-        F: Fn(&Self) -> Result<T, LocatorError> + 'static + Send + Sync,
-        T: Send + Sync + 'static,
+        F: Fn(&Locator<S>) -> Result<T, LocatorError> + 'static + Send + Sync,
+        T: Send + Sync + Clone + 'static,
     {
-        let provider = Provider::Factory(Box::new(move |locator| {
-            let value = factory(locator);
+        let cache = OnceLock::<T>::new();
+        let provider = Provider::Factory(Arc::new(move |locator| {
+            let value = match cache.get() {
+                Some(value) => Ok(value.clone()),
+                None => cycle::guard::<T, _>(|| factory(locator))
+                    .map(|value| cache.get_or_init(|| value).clone()),
+            };
+
             Box::new(value)
         }));
 
-        self.unchecked_insert(TypeId::of::<Result<T, LocatorError>>(), provider)
+        self.insert_verified(
+            TypeId::of::<Result<T, LocatorError>>(),
+            std::any::type_name::<Result<T, LocatorError>>(),
+            provider,
+            verify_fallible::<T, S>,
+        )
+    }
+
+    fn try_insert_with_err<F, T, E>(&mut self, factory: F) -> Option<Provider<S>>
+    where
+        F: Fn(&Locator<S>) -> Result<T, E> + Send + Sync + 'static,
+        T: Send + Sync + Clone + 'static,
+        E: Into<LocatorError>,
+    {
+        self.try_insert_with(move |locator| factory(locator).map_err(Into::into))
     }
 
     fn try_get<T>(&self) -> Result<T, LocatorError>
     where
         T: Send + Sync + 'static,
     {
-        let provider = self
-            .unchecked_get(&TypeId::of::<Result<T, LocatorError>>())
-            .ok_or(LocatorError::NotFound {
-                expected: std::any::type_name::<T>(),
-            })?;
-
-        match provider {
-            Provider::Single(f) => {
-                let value = f();
+        try_resolve(self)
+    }
+
+    fn try_insert_async_with<F, Fut, T>(&mut self, factory: F) -> Option<Provider<S>>
+    where
+        F: Fn(&Locator<S>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, LocatorError>> + Send + 'static,
+        T: Send + Sync + 'static,
+    {
+        let provider = Provider::AsyncFactory(Arc::new(move |locator| {
+            let fut = factory(locator);
+            Box::pin(async move {
+                let value: Box<dyn Any + Send + Sync> = Box::new(fut.await);
                 value
-                    .downcast::<Result<T, LocatorError>>()
-                    .map(|x| *x)
-                    .map_err(|_| LocatorError::NotFound {
-                        expected: std::any::type_name::<T>(),
-                    })
-                    .and_then(std::convert::identity)
+            })
+        }));
+
+        self.insert_verified(
+            TypeId::of::<Result<T, LocatorError>>(),
+            std::any::type_name::<Result<T, LocatorError>>(),
+            provider,
+            verify_fallible::<T, S>,
+        )
+    }
+
+    fn try_insert_async_with_retry<F, Fut, T>(
+        &mut self,
+        factory: F,
+        policy: RetryPolicy,
+    ) -> Option<Provider<S>>
+    where
+        F: Fn(&Locator<S>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, LocatorError>> + Send + 'static,
+        T: Send + Sync + Clone + 'static,
+    {
+        let cache = Arc::new(OnceLock::<T>::new());
+        let prev = self.try_insert_async_with(move |locator| {
+            let cache = cache.clone();
+            let fut = cache.get().is_none().then(|| factory(locator));
+            async move {
+                if let Some(fut) = fut {
+                    // If other caller initialized it first, its value is kept.
+                    let _ = cache.set(fut.await?);
+                }
+
+                Ok(cache.get().expect("value not initialized").clone())
             }
-            Provider::Factory(f) => {
-                let value = f(self);
-                value
-                    .downcast::<Result<T, LocatorError>>()
-                    .map(|x| *x)
-                    .map_err(|_| LocatorError::NotFound {
-                        expected: std::any::type_name::<T>(),
-                    })
-                    .and_then(std::convert::identity)
+        });
+
+        self.set_retry_policy(TypeId::of::<Result<T, LocatorError>>(), policy);
+        prev
+    }
+
+    async fn try_get_async<T>(&self) -> Result<T, LocatorError>
+    where
+        T: Send + Sync + 'static,
+    {
+        let id = TypeId::of::<Result<T, LocatorError>>();
+        let Some(provider) = self.unchecked_get(&id) else {
+            return self
+                .get_async::<T>()
+                .await
+                .ok_or_else(|| self.not_found::<T>());
+        };
+
+        match (provider, self.retry_policy(&id)) {
+            (Provider::AsyncFactory(f), Some(policy)) => {
+                policy
+                    .run(|| async { downcast_result(f(self).await) })
+                    .await
             }
+            (Provider::AsyncFactory(f), None) => downcast_result(f(self).await),
+            _ => self.try_get::<T>(),
         }
     }
 }
 
-impl sealed::Sealed for Locator {}
+/// Resolves a service inserted by `try_insert_with`, or inserted with other method if there is none,
+/// see `TryLocator::try_get`.
+pub(crate) fn try_resolve<T, S>(locator: &Locator<S>) -> Result<T, LocatorError>
+where
+    T: Send + Sync + 'static,
+    S: BuildHasher,
+{
+    let result = match locator.unchecked_get(&TypeId::of::<Result<T, LocatorError>>()) {
+        Some(provider) => locator
+            .resolve_fallible::<T>(provider)
+            .unwrap_or_else(|| Err(LocatorError::not_found::<T>())),
+        None => locator.get::<T>().ok_or_else(|| locator.not_found::<T>()),
+    };
+
+    result.map_err(cycle::while_resolving)
+}
+
+fn verify_fallible<T, S>(locator: &Locator<S>, provider: &Provider<S>) -> Result<(), LocatorError>
+where
+    T: Send + Sync + 'static,
+    S: BuildHasher,
+{
+    match provider {
+        Provider::Single(singleton) => downcast_result::<T>(singleton.get()).map(drop),
+        Provider::Cached(cached) => downcast_result::<T>(cached.get(locator)).map(drop),
+        Provider::Factory(f) => downcast_result::<T>(f(locator)).map(drop),
+        Provider::AsyncFactory(_) => Ok(()),
+    }
+}
+
+fn downcast_result<T>(value: Box<dyn Any + Send + Sync>) -> Result<T, LocatorError>
+where
+    T: Send + Sync + 'static,
+{
+    value
+        .downcast::<Result<T, LocatorError>>()
+        .map(|x| *x)
+        .map_err(|_| LocatorError::not_found::<T>())
+        .and_then(std::convert::identity)
+}
+
+impl<S> sealed::Sealed for Locator<S> {}
 
 pub(crate) mod sealed {
     pub trait Sealed {}
@@ -72,12 +246,19 @@ pub(crate) mod sealed {
 
 #[cfg(test)]
 mod tests {
-    use crate::{try_locator::TryLocator, Locator, LocatorError};
+    use crate::{try_locator::TryLocator, Locator, LocatorError, ResultExt, RetryPolicy};
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        time::Duration,
+    };
 
-    #[derive(Debug)]
+    #[derive(Debug, Clone)]
     struct ServiceA;
 
-    #[derive(Debug)]
+    #[derive(Debug, Clone)]
     struct ServiceB;
 
     #[test]
@@ -106,4 +287,268 @@ mod tests {
             LocatorError::NotFound { .. }
         ));
     }
+
+    #[test]
+    fn test_try_insert_with_propagates_errors() {
+        let mut locator = Locator::new();
+
+        locator.try_insert_with::<_, String>(|_| {
+            let value = std::fs::read_to_string("/this/file/does/not/exist").or_locator_err()?;
+            Ok(value)
+        });
+
+        locator.try_insert_with::<_, i32>(|_| {
+            let value = "not a number".parse::<i32>().map_err(LocatorError::other)?;
+            Ok(value)
+        });
+
+        let err = locator.try_get::<String>().unwrap_err();
+        assert!(err.downcast_other_ref::<std::io::Error>().is_some());
+
+        let err = locator.try_get::<i32>().unwrap_err();
+        assert!(err
+            .downcast_other_ref::<std::num::ParseIntError>()
+            .is_some());
+    }
+
+    #[test]
+    fn test_try_insert_with_err() {
+        #[derive(Debug)]
+        struct ConfigError;
+
+        impl std::fmt::Display for ConfigError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "invalid config")
+            }
+        }
+
+        impl std::error::Error for ConfigError {}
+
+        impl From<ConfigError> for LocatorError {
+            fn from(err: ConfigError) -> Self {
+                LocatorError::other(err)
+            }
+        }
+
+        let mut locator = Locator::new();
+        locator.try_insert_with_err(|_| Ok::<_, ConfigError>(ServiceA));
+        locator.try_insert_with_err(|_| Err::<ServiceB, _>(ConfigError));
+
+        assert!(locator.try_get::<ServiceA>().is_ok());
+
+        let err = locator.try_get::<ServiceB>().unwrap_err();
+        assert!(err.downcast_other_ref::<ConfigError>().is_some());
+        assert_eq!(err.to_string(), "invalid config");
+    }
+
+    #[test]
+    fn test_try_insert_with_resolves_dependencies() {
+        let mut locator = Locator::new();
+        locator.insert(String::from("postgres://localhost"));
+
+        locator.try_insert_with::<_, usize>(|locator| {
+            let url = locator
+                .get::<String>()
+                .ok_or_else(|| locator.not_found::<String>())?;
+            Ok(url.len())
+        });
+
+        assert_eq!(locator.try_get::<usize>().unwrap(), 20);
+    }
+
+    #[test]
+    fn test_try_insert_with_missing_dependency() {
+        #[derive(Debug, Clone)]
+        struct Pool;
+
+        let mut locator = Locator::new();
+        locator.try_insert_with::<_, ServiceB>(|locator| {
+            locator.try_get::<Pool>()?;
+            Ok(ServiceB)
+        });
+
+        locator.try_insert_with::<_, ServiceA>(|locator| {
+            locator.try_get::<ServiceB>()?;
+            Ok(ServiceA)
+        });
+
+        let err = locator.try_get::<ServiceA>().unwrap_err();
+        let LocatorError::WhileResolving { target, cause } = &err else {
+            panic!("expected a while resolving error, got: {err}");
+        };
+
+        assert!(target.ends_with("ServiceA"));
+        assert!(matches!(
+            cause.as_ref(),
+            LocatorError::WhileResolving { target, .. } if target.ends_with("ServiceB")
+        ));
+        assert!(matches!(
+            err.root_cause(),
+            LocatorError::NotFound { expected, .. } if expected.ends_with("Pool")
+        ));
+
+        let message = err.to_string();
+        assert!(message.starts_with("while resolving"));
+        assert!(message.contains("ServiceA"));
+        assert!(!message.contains("Pool"));
+    }
+
+    #[test]
+    fn test_try_get_falls_back_to_get() {
+        let mut locator = Locator::new();
+        locator.insert_with(|_| ServiceA);
+        locator.insert_with(|_| ServiceB);
+
+        assert!(locator.try_get::<ServiceA>().is_ok());
+        assert!(locator.try_get::<ServiceB>().is_ok());
+        assert!(matches!(
+            locator.try_get::<String>(),
+            Err(LocatorError::NotFound { .. })
+        ));
+
+        // The fallible service is preferred over the plain one.
+        locator.try_insert_with::<_, ServiceA>(|_| Err(LocatorError::other("unavailable")));
+        assert!(locator.try_get::<ServiceA>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_try_get_async_falls_back_to_get_async() {
+        let mut locator = Locator::new();
+        locator.insert_async_with(|_| async { ServiceA });
+
+        assert!(locator.try_get_async::<ServiceA>().await.is_ok());
+        assert!(locator.try_get_async::<ServiceB>().await.is_err());
+    }
+
+    #[test]
+    fn test_try_insert_with_runs_once() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut locator = Locator::new();
+
+        locator.try_insert_with::<_, ServiceA>({
+            let calls = calls.clone();
+            move |_| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Ok(ServiceA)
+            }
+        });
+
+        for _ in 0..10 {
+            assert!(locator.try_get::<ServiceA>().is_ok());
+        }
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_try_insert_with_runs_again_after_error() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut locator = Locator::new();
+
+        locator.try_insert_with::<_, ServiceA>({
+            let calls = calls.clone();
+            move |_| match calls.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(LocatorError::other("unavailable")),
+                _ => Ok(ServiceA),
+            }
+        });
+
+        assert!(locator.try_get::<ServiceA>().is_err());
+        assert!(locator.try_get::<ServiceA>().is_ok());
+        assert!(locator.try_get::<ServiceA>().is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_try_insert_with_cyclic() {
+        let mut locator = Locator::new();
+
+        locator.try_insert_with::<_, ServiceA>(|locator| {
+            locator.try_get::<ServiceB>()?;
+            Ok(ServiceA)
+        });
+
+        locator.try_insert_with::<_, ServiceB>(|locator| {
+            locator.try_get::<ServiceA>()?;
+            Ok(ServiceB)
+        });
+
+        let err = locator.try_get::<ServiceA>().unwrap_err();
+        let LocatorError::Cyclic { chain } = err else {
+            panic!("expected a cyclic error, got: {err}");
+        };
+
+        assert_eq!(chain.len(), 3);
+        assert_eq!(chain.first(), chain.last());
+    }
+
+    #[tokio::test]
+    async fn test_try_insert_async_with_retry() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut locator = Locator::new();
+
+        let delays = Arc::new(Mutex::new(Vec::new()));
+        let policy = RetryPolicy::new(3)
+            .with_delay(Duration::from_secs(1))
+            .with_multiplier(2)
+            .with_sleep({
+                let delays = delays.clone();
+                move |delay| delays.lock().unwrap().push(delay)
+            });
+
+        locator.try_insert_async_with_retry::<_, _, String>(
+            {
+                let calls = calls.clone();
+                move |_| {
+                    let calls = calls.clone();
+                    async move {
+                        if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                            Err(LocatorError::other("database is not ready"))
+                        } else {
+                            Ok(String::from("connected"))
+                        }
+                    }
+                }
+            },
+            policy,
+        );
+
+        assert!(locator.try_get_async::<String>().await.is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(
+            *delays.lock().unwrap(),
+            vec![Duration::from_secs(1), Duration::from_secs(2)]
+        );
+
+        // The value is cached after succeeding.
+        assert!(locator.try_get_async::<String>().await.is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        locator.try_insert_async_with_retry::<_, _, u32>(
+            |_| async { Err(LocatorError::other("unavailable")) },
+            RetryPolicy::new(2),
+        );
+
+        let err = locator.try_get_async::<u32>().await.unwrap_err();
+        assert_eq!(err.to_string(), "unavailable");
+    }
+
+    #[tokio::test]
+    async fn test_try_insert_async_with_and_try_get_async() {
+        let mut locator = Locator::new();
+
+        locator.try_insert_async_with::<_, _, ServiceA>(|_| async { Ok(ServiceA) });
+        locator.try_insert_async_with::<_, _, ServiceB>(|_| async {
+            Err(LocatorError::not_found::<ServiceB>())
+        });
+
+        assert!(locator.try_get_async::<ServiceA>().await.is_ok());
+        assert!(matches!(
+            locator.try_get_async::<ServiceB>().await,
+            Err(LocatorError::NotFound { .. })
+        ));
+
+        // Async services cannot be resolved synchronously.
+        assert!(locator.try_get::<ServiceA>().is_err());
+    }
 }
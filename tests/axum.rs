@@ -0,0 +1,48 @@
+#![cfg(feature = "axum")]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::get,
+    Extension, Router,
+};
+use kizuna::{axum::Inject, Locator};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+async fn greet(Inject(greeting): Inject<String>) -> String {
+    greeting
+}
+
+async fn count(Inject(count): Inject<u32>) -> String {
+    count.to_string()
+}
+
+fn app() -> Router {
+    let mut locator = Locator::new();
+    locator.insert(String::from("hello"));
+
+    Router::new()
+        .route("/greet", get(greet))
+        .route("/count", get(count))
+        .layer(Extension(Arc::new(locator)))
+}
+
+#[tokio::test]
+async fn inject_resolves_from_locator() {
+    let request = Request::get("/greet").body(Body::empty()).unwrap();
+    let response = app().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert_eq!(body, "hello");
+}
+
+#[tokio::test]
+async fn inject_missing_dependency_is_internal_server_error() {
+    let request = Request::get("/count").body(Body::empty()).unwrap();
+    let response = app().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
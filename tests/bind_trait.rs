@@ -0,0 +1,123 @@
+use kizuna::{bind_trait, Locator, LocatorError};
+
+trait UserRepository {
+    fn get_all(&self) -> Vec<String>;
+}
+
+#[derive(Clone, Default)]
+struct MemoryDb(Vec<String>);
+
+#[derive(Clone)]
+struct Prefix(&'static str);
+
+struct InMemoryUserRepository(MemoryDb);
+
+impl InMemoryUserRepository {
+    fn new(db: MemoryDb) -> Self {
+        InMemoryUserRepository(db)
+    }
+}
+
+impl UserRepository for InMemoryUserRepository {
+    fn get_all(&self) -> Vec<String> {
+        self.0 .0.clone()
+    }
+}
+
+struct PrefixedUserRepository(MemoryDb, Prefix);
+
+impl UserRepository for PrefixedUserRepository {
+    fn get_all(&self) -> Vec<String> {
+        self.0
+             .0
+            .iter()
+            .map(|name| format!("{}{name}", self.1 .0))
+            .collect()
+    }
+}
+
+#[test]
+fn bind_trait_resolves_dependencies() {
+    let mut locator = Locator::new();
+    locator.insert(MemoryDb(vec![String::from("alice")]));
+    bind_trait!(locator, UserRepository: Send + Sync, from |db: MemoryDb| InMemoryUserRepository::new(db));
+
+    let repo = locator
+        .get::<Box<dyn UserRepository + Send + Sync>>()
+        .unwrap();
+
+    assert_eq!(repo.get_all(), vec![String::from("alice")]);
+}
+
+#[test]
+fn bind_trait_with_many_dependencies() {
+    let mut locator = Locator::new();
+    locator.insert(MemoryDb(vec![String::from("bob")]));
+    locator.insert(Prefix("@"));
+    bind_trait!(locator, UserRepository: Send + Sync, from |db: MemoryDb, prefix: Prefix| {
+        PrefixedUserRepository(db, prefix)
+    });
+
+    let repo = locator
+        .get::<Box<dyn UserRepository + Send + Sync>>()
+        .unwrap();
+
+    assert_eq!(repo.get_all(), vec![String::from("@bob")]);
+}
+
+#[test]
+fn bind_trait_expands_to_the_bounds() {
+    let mut locator = Locator::new();
+    locator.insert(MemoryDb::default());
+    bind_trait!(locator, UserRepository: Send + Sync + Unpin, from |db: MemoryDb| InMemoryUserRepository(db));
+
+    assert!(locator
+        .get::<Box<dyn UserRepository + Send + Sync + Unpin>>()
+        .is_some());
+    assert!(locator
+        .get::<Box<dyn UserRepository + Send + Sync>>()
+        .is_none());
+}
+
+#[test]
+fn bind_trait_without_bounds() {
+    trait Plugin: Send + Sync {
+        fn name(&self) -> &'static str;
+    }
+
+    struct Auth;
+
+    impl Plugin for Auth {
+        fn name(&self) -> &'static str {
+            "auth"
+        }
+    }
+
+    let mut locator = Locator::new();
+    bind_trait!(locator, Plugin, from || Auth);
+
+    assert_eq!(locator.get::<Box<dyn Plugin>>().unwrap().name(), "auth");
+}
+
+#[test]
+fn bind_trait_fails_if_a_dependency_is_missing() {
+    let mut locator = Locator::new();
+    bind_trait!(locator, UserRepository: Send + Sync, from |db: MemoryDb| InMemoryUserRepository(db));
+
+    assert!(locator
+        .get::<Box<dyn UserRepository + Send + Sync>>()
+        .is_none());
+
+    let err = match locator.resolve::<Box<dyn UserRepository + Send + Sync>>() {
+        Ok(_) => panic!("expected the service to fail"),
+        Err(err) => err,
+    };
+
+    assert!(matches!(
+        err,
+        LocatorError::NotFound { expected, .. } if expected == std::any::type_name::<MemoryDb>()
+    ));
+
+    let errors = locator.verify().unwrap_err();
+    assert_eq!(errors.len(), 1);
+}
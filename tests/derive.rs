@@ -0,0 +1,17 @@
+#![cfg(feature = "derive")]
+
+#[test]
+fn derive_from_locator() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/derive_from_locator.rs");
+    t.pass("tests/ui/derive_from_locator_missing.rs");
+    t.compile_fail("tests/ui/derive_from_locator_invalid_attr.rs");
+}
+
+#[test]
+fn derive_inject() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/derive_inject.rs");
+    t.compile_fail("tests/ui/derive_inject_invalid_attr.rs");
+    t.compile_fail("tests/ui/derive_inject_conflicting_attrs.rs");
+}
@@ -0,0 +1,55 @@
+use kizuna::{insert_trait, Locator};
+
+trait UserRepository {
+    fn get_all(&self) -> Vec<String>;
+}
+
+#[derive(Clone, Default)]
+struct MemoryDb(Vec<String>);
+
+#[derive(Clone)]
+struct InMemoryUserRepository(MemoryDb);
+
+impl UserRepository for InMemoryUserRepository {
+    fn get_all(&self) -> Vec<String> {
+        self.0 .0.clone()
+    }
+}
+
+#[test]
+fn insert_trait_value() {
+    let mut locator = Locator::new();
+    let db = MemoryDb(vec![String::from("alice")]);
+    insert_trait!(
+        locator,
+        dyn UserRepository + Send + Sync,
+        InMemoryUserRepository(db)
+    );
+
+    let repo = locator
+        .get::<Box<dyn UserRepository + Send + Sync>>()
+        .unwrap();
+
+    assert_eq!(repo.get_all(), vec![String::from("alice")]);
+}
+
+#[test]
+fn insert_trait_factory() {
+    let mut locator = Locator::new();
+    locator.insert(MemoryDb(vec![String::from("bob")]));
+    insert_trait!(locator, dyn UserRepository + Send + Sync, |locator| {
+        InMemoryUserRepository(locator.get::<MemoryDb>().unwrap())
+    });
+
+    let repo = locator
+        .get::<Box<dyn UserRepository + Send + Sync>>()
+        .unwrap();
+
+    assert_eq!(repo.get_all(), vec![String::from("bob")]);
+}
+
+#[test]
+fn insert_trait_compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/insert_trait_not_implemented.rs");
+}
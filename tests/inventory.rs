@@ -0,0 +1,42 @@
+#![cfg(feature = "inventory")]
+
+use kizuna::{service, Locator};
+
+#[derive(Clone)]
+struct Config {
+    url: String,
+}
+
+struct Database {
+    url: String,
+}
+
+#[service]
+fn config() -> Config {
+    Config {
+        url: String::from("postgres://localhost"),
+    }
+}
+
+#[service]
+fn database(locator: &Locator) -> Database {
+    let config = locator.get::<Config>().unwrap();
+    Database { url: config.url }
+}
+
+#[test]
+fn from_inventory_registers_services() {
+    let locator = Locator::from_inventory();
+
+    assert_eq!(locator.get::<Config>().unwrap().url, "postgres://localhost");
+    assert_eq!(
+        locator.get::<Database>().unwrap().url,
+        "postgres://localhost"
+    );
+}
+
+#[test]
+fn service_rejects_other_arguments() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/service_invalid_argument.rs");
+}
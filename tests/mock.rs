@@ -0,0 +1,51 @@
+#![cfg(feature = "test-util")]
+
+use kizuna::{Locator, LocatorError};
+
+#[derive(Clone)]
+struct Config {
+    greeting: &'static str,
+}
+
+// Not `Clone`, like a connection to an external service.
+struct Mailer {
+    sent: Vec<String>,
+}
+
+fn handler(locator: &Locator, name: &str) -> Result<String, LocatorError> {
+    locator.invoke(|config: Config, mut mailer: Mailer| {
+        let message = format!("{}, {}!", config.greeting, name);
+        mailer.sent.push(message.clone());
+        message
+    })
+}
+
+#[test]
+fn test_handler_with_mock() {
+    let mut locator = Locator::mock();
+    locator.insert(Config { greeting: "Hello" });
+    locator.mock_insert(Mailer { sent: Vec::new() });
+
+    assert_eq!(handler(&locator, "Alice").unwrap(), "Hello, Alice!");
+    assert_eq!(locator.expect_resolved::<Config>().greeting, "Hello");
+}
+
+#[test]
+fn test_mock_insert_resolves_once() {
+    let mut locator = Locator::mock();
+    locator.insert(Config { greeting: "Hello" });
+    locator.mock_insert(Mailer { sent: Vec::new() });
+
+    assert!(locator.get::<Mailer>().is_some());
+    assert!(locator.get::<Mailer>().is_none());
+
+    let err = handler(&locator, "Bob").unwrap_err();
+    assert!(err.to_string().contains("already resolved"), "{err}");
+}
+
+#[test]
+#[should_panic(expected = "expected `u32` to be resolved")]
+fn test_expect_resolved_panics() {
+    let locator = Locator::mock();
+    locator.expect_resolved::<u32>();
+}
@@ -0,0 +1,21 @@
+#![cfg(feature = "serde")]
+
+use kizuna::Locator;
+
+#[derive(Clone)]
+struct Config {
+    port: u16,
+}
+
+#[test]
+fn test_serialize_locator_info() {
+    let mut locator = Locator::new();
+    locator.insert(Config { port: 8080 });
+    locator.insert_with(|locator| locator.get::<Config>().unwrap().port.to_string());
+
+    let json = serde_json::to_string(&locator.info()).unwrap();
+    assert!(json.contains(r#""type_name":"alloc::string::String""#));
+    assert!(json.contains(r#""type_name":"serde::Config""#));
+    assert!(json.contains(r#""kind":"Factory""#));
+    assert!(json.contains(r#""kind":"Single""#));
+}
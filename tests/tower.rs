@@ -0,0 +1,21 @@
+#![cfg(feature = "tower")]
+
+use http::Request;
+use kizuna::{tower::LocatorLayer, Locator};
+use std::{convert::Infallible, sync::Arc};
+use tower::{service_fn, Layer, ServiceExt};
+
+#[tokio::test]
+async fn locator_layer_injects_locator() {
+    let mut locator = Locator::new();
+    locator.insert(String::from("hello"));
+
+    let service =
+        LocatorLayer::new(Arc::new(locator)).layer(service_fn(|req: Request<()>| async move {
+            let locator = req.extensions().get::<Arc<Locator>>().unwrap();
+            Ok::<_, Infallible>(locator.get::<String>())
+        }));
+
+    let response = service.oneshot(Request::new(())).await.unwrap();
+    assert_eq!(response.as_deref(), Some("hello"));
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "tracing")]
+
+use kizuna::Locator;
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+#[derive(Clone, Default)]
+struct Logs(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Logs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+struct Config;
+
+#[derive(Clone)]
+struct Database;
+
+#[test]
+fn resolution_emits_events() {
+    let logs = Logs::default();
+    let writer = logs.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::TRACE)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+
+    let mut locator = Locator::new();
+    locator.insert(Config);
+    locator.insert_with(|locator| {
+        locator.get::<Config>().unwrap();
+        Database
+    });
+
+    tracing::subscriber::with_default(subscriber, || {
+        locator.get::<Database>().unwrap();
+    });
+
+    let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    assert!(logs.contains("resolving tracing::Database"));
+    assert!(logs.contains("resolved tracing::Database in"));
+
+    // The dependencies are resolved inside the span of the dependent service.
+    assert!(logs
+        .contains("resolve{service=\"tracing::Database\"}:resolve{service=\"tracing::Config\"}"));
+}
@@ -0,0 +1,31 @@
+use kizuna::{FromLocator, Locator};
+
+#[derive(Clone)]
+struct Config(&'static str);
+
+#[derive(FromLocator)]
+struct Service {
+    config: Config,
+    #[locator(named = "port")]
+    port: u16,
+    #[locator(default)]
+    retries: u32,
+}
+
+#[derive(FromLocator)]
+struct Tuple(Config, #[locator(default)] Option<u8>);
+
+fn main() {
+    let mut locator = Locator::new();
+    locator.insert(Config("config"));
+    locator.insert_named("port", 8080_u16);
+
+    let service = Service::from_locator(&locator).unwrap();
+    assert_eq!(service.config.0, "config");
+    assert_eq!(service.port, 8080);
+    assert_eq!(service.retries, 0);
+
+    let tuple = Tuple::from_locator(&locator).unwrap();
+    assert_eq!(tuple.0 .0, "config");
+    assert_eq!(tuple.1, None);
+}
@@ -0,0 +1,12 @@
+use kizuna::FromLocator;
+
+#[derive(Clone)]
+struct Config;
+
+#[derive(FromLocator)]
+struct Service {
+    #[locator(unknown)]
+    config: Config,
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+use kizuna::{FromLocator, Locator, LocatorError};
+
+#[derive(Clone)]
+struct Config;
+
+#[derive(Clone)]
+struct Pool;
+
+#[derive(FromLocator)]
+#[allow(dead_code)]
+struct Service {
+    config: Config,
+    pool: Pool,
+}
+
+fn main() {
+    let mut locator = Locator::new();
+    locator.insert(Config);
+
+    match Service::from_locator(&locator) {
+        Err(LocatorError::NotFound { expected, .. }) => assert!(expected.ends_with("Pool")),
+        _ => panic!("expected a not found error"),
+    }
+}
@@ -0,0 +1,60 @@
+use kizuna::{Inject, Locator, LocatorError};
+use std::hash::{BuildHasher, BuildHasherDefault, DefaultHasher};
+
+#[derive(Clone)]
+struct Config(&'static str);
+
+#[derive(Clone)]
+struct Port(u16);
+
+struct Address(String);
+
+impl From<Port> for Address {
+    fn from(port: Port) -> Self {
+        Address(format!("localhost:{}", port.0))
+    }
+}
+
+fn load_name<S: BuildHasher>(locator: &Locator<S>) -> Result<String, LocatorError> {
+    let config = locator
+        .get::<Config>()
+        .ok_or_else(|| locator.not_found::<Config>())?;
+    Ok(config.0.to_uppercase())
+}
+
+#[derive(Inject)]
+struct Service {
+    config: Config,
+    #[inject(with = load_name)]
+    name: String,
+    #[inject(clone_from = Port)]
+    address: Address,
+}
+
+#[derive(Inject)]
+struct Tuple(Config, #[inject(clone_from = Port)] Address);
+
+fn main() {
+    let mut locator = Locator::new();
+    assert!(Service::inject(&locator).is_err());
+
+    locator.insert(Config("config"));
+    locator.insert(Port(8080));
+
+    let service = Service::inject(&locator).unwrap();
+    assert_eq!(service.config.0, "config");
+    assert_eq!(service.name, "CONFIG");
+    assert_eq!(service.address.0, "localhost:8080");
+
+    let tuple = Tuple::inject(&locator).unwrap();
+    assert_eq!(tuple.0 .0, "config");
+    assert_eq!(tuple.1 .0, "localhost:8080");
+
+    let mut locator = Locator::with_hasher(BuildHasherDefault::<DefaultHasher>::default());
+    locator.insert(Config("custom"));
+    locator.insert(Port(80));
+
+    let service = Service::inject(&locator).unwrap();
+    assert_eq!(service.name, "CUSTOM");
+    assert_eq!(service.address.0, "localhost:80");
+}
@@ -0,0 +1,16 @@
+use kizuna::{Inject, Locator, LocatorError};
+
+#[derive(Clone)]
+struct Config;
+
+fn load_config(_: &Locator) -> Result<Config, LocatorError> {
+    Ok(Config)
+}
+
+#[derive(Inject)]
+struct Service {
+    #[inject(with = load_config, clone_from = Config)]
+    config: Config,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use kizuna::Inject;
+
+#[derive(Clone)]
+struct Config;
+
+#[derive(Inject)]
+struct Service {
+    #[inject(unknown)]
+    config: Config,
+}
+
+fn main() {}
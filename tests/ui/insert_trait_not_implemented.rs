@@ -0,0 +1,11 @@
+use kizuna::{insert_trait, Locator};
+
+trait UserRepository {}
+
+#[derive(Clone)]
+struct NotARepository;
+
+fn main() {
+    let mut locator = Locator::new();
+    insert_trait!(locator, dyn UserRepository + Send + Sync, NotARepository);
+}
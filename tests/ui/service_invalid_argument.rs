@@ -0,0 +1,11 @@
+use kizuna::service;
+
+#[derive(Clone)]
+struct Port(u16);
+
+#[service]
+fn port(value: u16) -> Port {
+    Port(value)
+}
+
+fn main() {}